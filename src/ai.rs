@@ -0,0 +1,460 @@
+//! Computer-controlled snakes. Every `Snake` of a game is driven by a `Controller`, which looks
+//! at a `View` of the area on each tick and tells where to head next. The human player is driven
+//! the same way, by the turns they type; the computer players follow one of the `Strategy`s here.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+use crate::config::Difficulty;
+use crate::sim::{BoardMode, Coordinate, Direction, Simulation, StepOutcome, View};
+
+
+const DIRECTIONS: [Direction; 4] = [Direction::LEFT, Direction::RIGHT, Direction::UP, Direction::DOWN];
+
+
+/// Drives a `Snake`, one tick at a time.
+pub trait Controller {
+    /// Where the `Snake` of `view` should head on the next tick, or `None` to keep going.
+    fn decide(&mut self, view: &View) -> Option<Direction>;
+
+    /// A turn to `direction` requested by the player, while the `Snake` heads to `current`, for
+    /// the controllers driven by one. Computer players ignore it.
+    fn input(&mut self, _direction: Direction, _current: Direction) {}
+
+    /// Who drives, as shown to the players.
+    fn name(&self) -> &str;
+}
+
+
+/// Lets `controller` play `sim` until the game is over, or `max_ticks` ticks have been played
+/// since it started. Returns the outcome of the last tick played, if any.
+pub fn play(sim: &mut Simulation, controller: &mut dyn Controller, max_ticks: u64) -> Option<StepOutcome> {
+    let mut outcome = None;
+
+    while !sim.over() && sim.ticks() < max_ticks {
+        let input = controller.decide(&sim.view());
+        outcome = Some(sim.step(input));
+    }
+
+    outcome
+}
+
+
+/// The ways the computer can play.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Strategy {
+    /// Always heads for the food, as long as the next cell is free. See `Greedy`.
+    GREEDY,
+    /// Takes the shortest path to the food when it is safe. See `AStar`.
+    ASTAR,
+    /// Follows a cycle through every cell. See `Hamiltonian`.
+    HAMILTONIAN,
+}
+
+
+impl Strategy {
+
+    pub const ALL: [Strategy; 3] = [Strategy::GREEDY, Strategy::ASTAR, Strategy::HAMILTONIAN];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Strategy::GREEDY      => "greedy",
+            Strategy::ASTAR       => "astar",
+            Strategy::HAMILTONIAN => "hamiltonian",
+        }
+    }
+
+    /// Finds a strategy by name, ignoring case.
+    pub fn from_name(name: &str) -> Option<Strategy> {
+        Strategy::ALL.into_iter().find(|s| s.name().eq_ignore_ascii_case(name))
+    }
+
+    /// How the computer players play at `difficulty`. The Hamiltonian cycle never gets stuck,
+    /// but it is too slow to win many races for the food.
+    pub fn for_difficulty(difficulty: Difficulty) -> Strategy {
+        match difficulty {
+            Difficulty::EASY   => Strategy::GREEDY,
+            Difficulty::NORMAL => Strategy::HAMILTONIAN,
+            Difficulty::HARD   => Strategy::ASTAR,
+        }
+    }
+
+    /// A new `Controller` playing this strategy.
+    pub fn controller(self) -> Box<dyn Controller> {
+        match self {
+            Strategy::GREEDY      => Box::new(Greedy),
+            Strategy::ASTAR       => Box::new(AStar),
+            Strategy::HAMILTONIAN => Box::new(Hamiltonian::new()),
+        }
+    }
+}
+
+
+/// The cells a `Snake` cannot move into on the next tick: the walls and every body. The tail of
+/// the `Snake` deciding is left out, since it moves out of the way.
+#[derive(Clone)]
+struct Obstacles {
+    hcells      : u32,
+    blocked     : Vec<bool>,
+}
+
+
+impl Obstacles {
+
+    fn new(view: &View) -> Obstacles {
+        let mut obstacles = Obstacles {
+            hcells  : view.hcells,
+            blocked : vec![false; view.hcells as usize * view.vcells as usize],
+        };
+
+        for c in view.walls.iter().chain(view.snakes.iter().flat_map(|s| &s.body)) {
+            obstacles.set(*c, true);
+        }
+
+        let tail = *view.snake().body.last().unwrap();
+        obstacles.set(tail, false);
+        obstacles
+    }
+
+    fn index(&self, c: Coordinate) -> usize {
+        c.y as usize * self.hcells as usize + c.x as usize
+    }
+
+    fn blocked(&self, c: Coordinate) -> bool {
+        self.blocked[self.index(c)]
+    }
+
+    fn set(&mut self, c: Coordinate, blocked: bool) {
+        let i = self.index(c);
+        self.blocked[i] = blocked;
+    }
+}
+
+
+/// The moves the `Snake` of `view` can make without running into the edge or an obstacle, with
+/// the cell each one leads to. Going on straight comes first.
+fn safe_moves(view: &View, obstacles: &Obstacles) -> Vec<(Direction, Coordinate)> {
+    let snake = view.snake();
+
+    let mut directions = vec![snake.direction];
+    directions.extend(DIRECTIONS.iter().filter(|d| **d != snake.direction && **d != snake.direction.opposite()));
+
+    directions.into_iter()
+        .filter_map(|d| view.next_cell(snake.head(), d).map(|c| (d, c)))
+        .filter(|(_, c)| !obstacles.blocked(*c))
+        .collect()
+}
+
+
+/// Number of moves from `a` to `b` on an empty area, going through the edges when they are open.
+fn distance(view: &View, a: Coordinate, b: Coordinate) -> u32 {
+    let (dx, dy) = (a.x.abs_diff(b.x), a.y.abs_diff(b.y));
+
+    match view.mode {
+        BoardMode::WALLS => dx + dy,
+        BoardMode::WRAP  => dx.min(view.hcells - dx) + dy.min(view.vcells - dy),
+    }
+}
+
+
+/// Number of cells that can be reached from `from` without going through an obstacle.
+fn room(view: &View, obstacles: &Obstacles, from: Coordinate) -> usize {
+    let mut seen = vec![false; obstacles.blocked.len()];
+    let mut queue = VecDeque::from([from]);
+    seen[obstacles.index(from)] = true;
+
+    let mut count = 0;
+    while let Some(cell) = queue.pop_front() {
+        count += 1;
+
+        for next in DIRECTIONS.iter().filter_map(|d| view.next_cell(cell, *d)) {
+            let i = obstacles.index(next);
+            if !seen[i] && !obstacles.blocked(next) {
+                seen[i] = true;
+                queue.push_back(next);
+            }
+        }
+    }
+
+    count
+}
+
+
+/// The shortest way from `from` to `to` without going through an obstacle, as the moves to make
+/// and the cell each one leads to. Found with A*, guided by the `distance` left.
+fn find_path(view: &View, obstacles: &Obstacles, from: Coordinate, to: Coordinate) -> Option<Vec<(Direction, Coordinate)>> {
+    let cells = obstacles.blocked.len();
+    let mut cost = vec![u32::MAX; cells];
+    let mut came_from: Vec<Option<(Direction, Coordinate)>> = vec![None; cells];
+    let mut open = BinaryHeap::new();
+
+    cost[obstacles.index(from)] = 0;
+    open.push(Reverse((distance(view, from, to), 0, obstacles.index(from))));
+
+    while let Some(Reverse((_, steps, index))) = open.pop() {
+        let cell = Coordinate { x: index as u32 % view.hcells, y: index as u32 / view.hcells };
+        if cell == to {
+            break;
+        }
+
+        if steps > cost[index] {
+            continue;
+        }
+
+        for direction in DIRECTIONS {
+            let next = match view.next_cell(cell, direction) {
+                Some(next) if !obstacles.blocked(next) => next,
+                _ => continue,
+            };
+
+            let i = obstacles.index(next);
+            if steps + 1 < cost[i] {
+                cost[i] = steps + 1;
+                came_from[i] = Some((direction, cell));
+                open.push(Reverse((steps + 1 + distance(view, next, to), steps + 1, i)));
+            }
+        }
+    }
+
+    if from == to || came_from[obstacles.index(to)].is_none() {
+        return None;
+    }
+
+    let mut path = Vec::new();
+    let mut cell = to;
+    while cell != from {
+        let (direction, previous) = came_from[obstacles.index(cell)].unwrap();
+        path.push((direction, cell));
+        cell = previous;
+    }
+
+    path.reverse();
+    Some(path)
+}
+
+
+/// The safe move leading to the most room, if there is any safe move left.
+fn most_room(view: &View, obstacles: &Obstacles) -> Option<Direction> {
+    safe_moves(view, obstacles).into_iter()
+        .min_by_key(|(_, c)| Reverse(room(view, obstacles, *c)))
+        .map(|(d, _)| d)
+}
+
+
+/// Heads straight for the food: of the moves that don't run into anything right away, it takes
+/// the one that gets closest. It easily gets trapped in its own coils.
+pub struct Greedy;
+
+
+impl Controller for Greedy {
+
+    fn decide(&mut self, view: &View) -> Option<Direction> {
+        let obstacles = Obstacles::new(view);
+        let moves = safe_moves(view, &obstacles);
+
+        let best = match view.food {
+            Some(food) => moves.iter().min_by_key(|(_, c)| distance(view, *c, food)),
+            None => moves.first(),
+        };
+
+        best.map(|(d, _)| *d)
+    }
+
+    fn name(&self) -> &str {
+        "Greedy"
+    }
+}
+
+
+/// Takes the shortest path to the food, found with A*, but only if the `Snake` could still
+/// reach its own tail once there: following the tail always leaves a way out, so it never walls
+/// itself in. When the food is not safe to get, it goes the long way round its tail until it is;
+/// as a last resort, it goes wherever there is the most room. It keeps away from the cells other
+/// heads could move into, when it can.
+pub struct AStar;
+
+
+impl AStar {
+
+    /// Whether, after following `path` to the food, the `Snake` of `view` could still reach its
+    /// tail. Other snakes are assumed to stay where they are.
+    fn safe(view: &View, obstacles: &Obstacles, path: &[(Direction, Coordinate)]) -> bool {
+        let snake = view.snake();
+
+        // The body once there: the cells of the path, then the old body, one cell longer since
+        // the `Snake` grows when it eats.
+        let mut body: Vec<Coordinate> = path.iter().rev().map(|(_, c)| *c).collect();
+        body.extend(&snake.body);
+        body.truncate(snake.body.len() + 1);
+
+        let mut after = obstacles.clone();
+        for c in &snake.body {
+            after.set(*c, false);
+        }
+        for c in &body[..body.len() - 1] {
+            after.set(*c, true);
+        }
+
+        find_path(view, &after, body[0], body[body.len() - 1]).is_some()
+    }
+
+    /// The move to make with `obstacles` in the way.
+    fn plan(view: &View, obstacles: &Obstacles) -> Option<Direction> {
+        let snake = view.snake();
+
+        if let Some(food) = view.food {
+            if let Some(path) = find_path(view, obstacles, snake.head(), food) {
+                if AStar::safe(view, obstacles, &path) {
+                    return Some(path[0].0);
+                }
+            }
+        }
+
+        // Of the moves that keep the tail within reach, the one furthest from it takes the long
+        // way round, which changes the shape of the body until the food is safe to get.
+        let tail = *snake.body.last().unwrap();
+        let chase = safe_moves(view, obstacles).into_iter()
+            .filter(|(d, c)| AStar::safe(view, obstacles, &[(*d, *c)]))
+            .min_by_key(|(_, c)| Reverse(distance(view, *c, tail)));
+
+        match chase {
+            Some((direction, _)) => Some(direction),
+            None => most_room(view, obstacles),
+        }
+    }
+}
+
+
+impl Controller for AStar {
+
+    fn decide(&mut self, view: &View) -> Option<Direction> {
+        // Cells another head could move into on the next tick risk a head-on collision: they
+        // are avoided whenever there is another way.
+        let mut cautious = Obstacles::new(view);
+        for (_, snake) in view.snakes.iter().enumerate().filter(|(i, _)| *i != view.me) {
+            for c in DIRECTIONS.iter().filter_map(|d| view.next_cell(snake.head(), *d)) {
+                cautious.set(c, true);
+            }
+        }
+
+        AStar::plan(view, &cautious).or_else(|| AStar::plan(view, &Obstacles::new(view)))
+    }
+
+    fn name(&self) -> &str {
+        "A*"
+    }
+}
+
+
+/// A cycle through every cell of an area of `hcells` x `vcells` cells, as the direction to
+/// take from each cell (indexed as `y * hcells + x`). The rows are swept back and forth, leaving
+/// out the first column, which leads back to the start. This needs an even number of rows, or
+/// of columns by sweeping these instead.
+fn hamiltonian_cycle(hcells: u32, vcells: u32) -> Option<Vec<Direction>> {
+    if hcells < 2 || vcells < 2 {
+        return None;
+    }
+
+    if vcells % 2 == 1 {
+        if hcells % 2 == 1 {
+            return None;
+        }
+
+        // Built on the area turned sideways, then turned back.
+        let turned = hamiltonian_cycle(vcells, hcells)?;
+        let cycle = (0..hcells * vcells)
+            .map(|i| match turned[((i % hcells) * vcells + i / hcells) as usize] {
+                Direction::LEFT  => Direction::UP,
+                Direction::RIGHT => Direction::DOWN,
+                Direction::UP    => Direction::LEFT,
+                Direction::DOWN  => Direction::RIGHT,
+            })
+            .collect();
+
+        return Some(cycle);
+    }
+
+    let cycle = (0..hcells * vcells)
+        .map(|i| {
+            let (x, y) = (i % hcells, i / hcells);
+
+            if x == 0 {
+                if y == 0 { Direction::RIGHT } else { Direction::UP }
+            }
+            else if y % 2 == 0 {
+                if x < hcells - 1 { Direction::RIGHT } else { Direction::DOWN }
+            }
+            else if x > 1 || y == vcells - 1 {
+                Direction::LEFT
+            }
+            else {
+                Direction::DOWN
+            }
+        })
+        .collect();
+
+    Some(cycle)
+}
+
+
+/// Follows a cycle through every cell of the area: it can never run into itself, and it ends up
+/// filling the whole area, slowly. There is no such cycle on areas with walls, or an odd number
+/// of both rows and columns; there, and whenever the way along the cycle is blocked, it plays
+/// like `AStar`.
+pub struct Hamiltonian {
+    /// The cycle for the area last seen, and the size of that area.
+    cycle       : Option<Vec<Direction>>,
+    size        : (u32, u32),
+}
+
+
+impl Hamiltonian {
+
+    pub fn new() -> Hamiltonian {
+        Hamiltonian { cycle: None, size: (0, 0) }
+    }
+}
+
+
+impl Default for Hamiltonian {
+    fn default() -> Hamiltonian {
+        Hamiltonian::new()
+    }
+}
+
+
+impl Controller for Hamiltonian {
+
+    fn decide(&mut self, view: &View) -> Option<Direction> {
+        if self.size != (view.hcells, view.vcells) {
+            self.size  = (view.hcells, view.vcells);
+            self.cycle = hamiltonian_cycle(view.hcells, view.vcells);
+        }
+
+        let cycle = match &self.cycle {
+            Some(cycle) if view.walls.is_empty() => cycle,
+            _ => return AStar.decide(view),
+        };
+
+        let snake = view.snake();
+        let along = |c: Coordinate| {
+            let direction = cycle[c.y as usize * view.hcells as usize + c.x as usize];
+            (direction, view.next_cell(c, direction))
+        };
+
+        // Once the whole body lies along the cycle, following it is always safe. Until then, the
+        // `Snake` joins it whenever that leaves a way out.
+        let on_cycle = snake.body.windows(2).all(|pair| along(pair[1]).1 == Some(pair[0]));
+
+        let obstacles = Obstacles::new(view);
+        match along(snake.head()) {
+            (direction, Some(next)) if direction != snake.direction.opposite() && !obstacles.blocked(next) &&
+                                       (on_cycle || AStar::safe(view, &obstacles, &[(direction, next)])) => Some(direction),
+            _ => AStar.decide(view),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "Hamiltonian"
+    }
+}
@@ -0,0 +1,255 @@
+//! Benchmarks of the computer players: every `Strategy` plays many games without a display,
+//! each one from its own seed, and the results are summed up in a `Report` per strategy. The
+//! games are spread over several threads, but they are picked by seed, so the reports are the
+//! same whatever the number of threads. Reports can be written as CSV or JSON, to compare
+//! changes to the strategies objectively.
+
+use std::thread;
+
+use crate::ai::{self, Strategy};
+use crate::level::Level;
+use crate::sim::{BoardMode, Collision, Simulation};
+
+
+/// What a `Snake` can die of when it plays alone, in the order they are reported.
+const CAUSES: [Collision; 3] = [Collision::EDGE, Collision::WALL, Collision::BODY];
+
+
+/// The games to play.
+#[derive(Debug, Clone)]
+pub struct BenchConfig {
+    pub strategies  : Vec<Strategy>,
+    /// Number of games every strategy plays.
+    pub games       : u32,
+    /// Seed of the first game; the next ones use the following seeds.
+    pub seed        : u64,
+    /// Number of threads the games are spread over.
+    pub threads     : usize,
+    /// The area the games are played on.
+    pub level       : Level,
+    pub mode        : BoardMode,
+    /// Ticks after which a game is stopped, if the `Snake` is still alive.
+    pub max_ticks   : u64,
+}
+
+
+/// Ticks after which a game on an area of `hcells` x `vcells` cells is stopped by default. A
+/// `Snake` may go through the whole area before reaching every food, so it takes up to the area
+/// squared to fill it.
+pub fn default_max_ticks(hcells: u32, vcells: u32) -> u64 {
+    let area = hcells as u64 * vcells as u64;
+    area.saturating_mul(area)
+}
+
+
+impl BenchConfig {
+
+    /// Every strategy playing 1000 games from seed 1, on an open area of `hcells` x `vcells`
+    /// cells with walls around, on as many threads as there are CPUs. Games are stopped after
+    /// `default_max_ticks`.
+    pub fn new(hcells: u32, vcells: u32) -> BenchConfig {
+        BenchConfig {
+            strategies  : Strategy::ALL.to_vec(),
+            games       : 1000,
+            seed        : 1,
+            threads     : thread::available_parallelism().map_or(1, |n| n.get()),
+            level       : Level::open(hcells, vcells),
+            mode        : BoardMode::WALLS,
+            max_ticks   : default_max_ticks(hcells, vcells),
+        }
+    }
+}
+
+
+/// How a single game went.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct GameResult {
+    pub seed        : u64,
+    pub score       : u32,
+    /// Number of ticks played.
+    pub ticks       : u64,
+    /// What the `Snake` died of, if it did.
+    pub collision   : Option<Collision>,
+    /// Whether the `Snake` filled the whole area.
+    pub won         : bool,
+}
+
+
+/// Plays a game of `strategy` from `seed`, as `config` says. Fails if the `Snake` does not fit
+/// in the level.
+pub fn play_game(config: &BenchConfig, strategy: Strategy, seed: u64) -> Result<GameResult, String> {
+    let mut sim = Simulation::from_level(config.level.clone(), config.mode, seed)?;
+    let mut controller = strategy.controller();
+
+    ai::play(&mut sim, controller.as_mut(), config.max_ticks);
+
+    Ok(GameResult {
+        seed,
+        score       : sim.score(),
+        ticks       : sim.ticks(),
+        collision   : sim.collision(),
+        won         : sim.over() && sim.collision().is_none(),
+    })
+}
+
+
+/// The results of the games played by a `Strategy`.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Report {
+    pub strategy    : Strategy,
+    pub games       : u32,
+    pub mean_score  : f64,
+    pub median_score: f64,
+    pub max_score   : u32,
+    /// Share of the games in which the `Snake` filled the whole area, from 0 to 1.
+    pub win_rate    : f64,
+    /// Ticks played before dying, over the games in which the `Snake` died; 0 if it never did.
+    pub mean_steps_to_death     : f64,
+    pub median_steps_to_death   : f64,
+    /// Number of games stopped after `BenchConfig::max_ticks`, with the `Snake` still alive.
+    pub timeouts    : u32,
+    /// Number of games lost to every cause of death, in the order of `CAUSES`.
+    pub deaths      : Vec<(Collision, u32)>,
+}
+
+
+fn mean(values: &[f64]) -> f64 {
+    match values.len() {
+        0 => 0.0,
+        n => values.iter().sum::<f64>() / n as f64,
+    }
+}
+
+
+fn median(values: &[f64]) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+
+    match sorted.len() {
+        0 => 0.0,
+        n if n % 2 == 1 => sorted[n / 2],
+        n => (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0,
+    }
+}
+
+
+impl Report {
+
+    /// Sums up the `results` of the games played by `strategy`.
+    pub fn new(strategy: Strategy, results: &[GameResult]) -> Report {
+        let scores: Vec<f64> = results.iter().map(|r| r.score as f64).collect();
+        let steps: Vec<f64> = results.iter()
+            .filter(|r| r.collision.is_some())
+            .map(|r| r.ticks as f64)
+            .collect();
+        let wins = results.iter().filter(|r| r.won).count();
+
+        Report {
+            strategy,
+            games           : results.len() as u32,
+            mean_score      : mean(&scores),
+            median_score    : median(&scores),
+            max_score       : results.iter().map(|r| r.score).max().unwrap_or(0),
+            win_rate        : if results.is_empty() { 0.0 } else { wins as f64 / results.len() as f64 },
+            mean_steps_to_death     : mean(&steps),
+            median_steps_to_death   : median(&steps),
+            timeouts        : results.iter().filter(|r| !r.won && r.collision.is_none()).count() as u32,
+            deaths          : CAUSES.iter()
+                .map(|cause| (*cause, results.iter().filter(|r| r.collision == Some(*cause)).count() as u32))
+                .collect(),
+        }
+    }
+}
+
+
+/// Plays every game of `strategy`, spread over the threads of `config`. The results come in
+/// the order of their seeds.
+pub fn play_games(config: &BenchConfig, strategy: Strategy) -> Result<Vec<GameResult>, String> {
+    let threads = config.threads.max(1) as u64;
+    let seeds: Vec<u64> = (0..config.games as u64).map(|i| config.seed.wrapping_add(i)).collect();
+
+    let results: Result<Vec<Vec<GameResult>>, String> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|t| {
+                let seeds = &seeds;
+                scope.spawn(move || {
+                    seeds.iter()
+                        .skip(t as usize)
+                        .step_by(threads as usize)
+                        .map(|seed| play_game(config, strategy, *seed))
+                        .collect::<Result<Vec<_>, _>>()
+                })
+            })
+            .collect();
+
+        workers.into_iter()
+            .map(|worker| worker.join().expect("a benchmark thread panicked"))
+            .collect()
+    });
+
+    let mut results: Vec<GameResult> = results?.into_iter().flatten().collect();
+    results.sort_by_key(|r| r.seed.wrapping_sub(config.seed));
+    Ok(results)
+}
+
+
+/// Plays the games of every strategy of `config`, and reports how each one did.
+pub fn run(config: &BenchConfig) -> Result<Vec<Report>, String> {
+    config.strategies.iter()
+        .map(|strategy| Ok(Report::new(*strategy, &play_games(config, *strategy)?)))
+        .collect()
+}
+
+
+/// The name of a cause of death, as written in the reports.
+pub fn cause_name(cause: Collision) -> &'static str {
+    match cause {
+        Collision::EDGE  => "edge",
+        Collision::WALL  => "wall",
+        Collision::BODY  => "body",
+        Collision::SNAKE => "snake",
+        Collision::HEAD  => "head",
+    }
+}
+
+
+/// The `reports` as CSV, with a header line and one line per strategy.
+pub fn to_csv(reports: &[Report]) -> String {
+    let mut text = String::from("strategy,games,mean_score,median_score,max_score,win_rate,mean_steps_to_death,median_steps_to_death,timeouts");
+    for cause in CAUSES {
+        text.push_str(&format!(",deaths_{}", cause_name(cause)));
+    }
+    text.push('\n');
+
+    for r in reports {
+        text.push_str(&format!("{},{},{:.2},{:.1},{},{:.4},{:.2},{:.1},{}",
+                               r.strategy.name(), r.games, r.mean_score, r.median_score, r.max_score,
+                               r.win_rate, r.mean_steps_to_death, r.median_steps_to_death, r.timeouts));
+        for (_, count) in &r.deaths {
+            text.push_str(&format!(",{}", count));
+        }
+        text.push('\n');
+    }
+
+    text
+}
+
+
+/// The `reports` as a JSON array, with an object per strategy.
+pub fn to_json(reports: &[Report]) -> String {
+    let objects: Vec<String> = reports.iter()
+        .map(|r| {
+            let deaths: Vec<String> = r.deaths.iter()
+                .map(|(cause, count)| format!("\"{}\": {}", cause_name(*cause), count))
+                .collect();
+
+            format!("  {{\"strategy\": \"{}\", \"games\": {}, \"mean_score\": {:.2}, \"median_score\": {:.1}, \
+                     \"max_score\": {}, \"win_rate\": {:.4}, \"mean_steps_to_death\": {:.2}, \
+                     \"median_steps_to_death\": {:.1}, \"timeouts\": {}, \"deaths\": {{{}}}}}",
+                    r.strategy.name(), r.games, r.mean_score, r.median_score, r.max_score, r.win_rate,
+                    r.mean_steps_to_death, r.median_steps_to_death, r.timeouts, deaths.join(", "))
+        })
+        .collect();
+
+    format!("[\n{}\n]\n", objects.join(",\n"))
+}
@@ -0,0 +1,191 @@
+use std::fs;
+use std::process;
+
+use snake_rust::ai::Strategy;
+use snake_rust::cli::parse_value;
+use snake_rust::bench::{self, BenchConfig};
+use snake_rust::config::{DEFAULT_HCELLS, DEFAULT_VCELLS, MAX_HCELLS, MAX_VCELLS, MIN_HCELLS, MIN_VCELLS};
+use snake_rust::level::Level;
+use snake_rust::sim::BoardMode;
+
+const USAGE: &str = "\
+Usage: snake-bench [OPTIONS]
+
+Plays many seeded games with every built-in bot, without a display, and reports how each one
+did: mean, median and best score, win rate, steps to death and causes of death.
+
+Options:
+    --strategy <NAME>   Bot to benchmark: greedy, astar or hamiltonian; repeat it to pick
+                        several. All of them by default
+    --games <N>         Number of games every bot plays (default: 1000)
+    --seed <NUMBER>     Seed of the first game; the next ones use the following seeds
+                        (default: 1)
+    --threads <N>       Number of threads the games are spread over (default: one per CPU)
+    --width <CELLS>     Width of the board, in cells
+    --height <CELLS>    Height of the board, in cells
+    --wrap              Leaving the board on one side enters it on the opposite side
+    --level <LEVEL>     Play on a level file, or on one of the bundled levels by name
+    --max-ticks <N>     Stop a game after this many ticks (default: the number of cells of
+                        the board, squared)
+    --format <FORMAT>   csv or json (default: csv)
+    --output <FILE>     Write the report to a file instead of the standard output
+    -h, --help          Print this help
+    -V, --version       Print the version
+";
+
+/// How the reports are written.
+enum Format {
+    Csv,
+    Json,
+}
+
+/// What the user asked for on the command line.
+enum Command {
+    /// Run the benchmark, and write the reports in the format given, to the file given if any.
+    Bench(BenchConfig, Format, Option<String>),
+    Help,
+    Version,
+}
+
+/// Entry point.
+fn main() {
+    let command = match parse_args(std::env::args().skip(1)) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("snake-bench: {}", e);
+            eprintln!("Try 'snake-bench --help' for more information.");
+            process::exit(2);
+        }
+    };
+
+    let result = match command {
+        Command::Bench(config, format, output) => {
+            bench::run(&config).and_then(|reports| {
+                let text = match format {
+                    Format::Csv  => bench::to_csv(&reports),
+                    Format::Json => bench::to_json(&reports),
+                };
+
+                match output {
+                    Some(path) => fs::write(&path, text).map_err(|e| format!("could not write {}: {}", path, e)),
+                    None => {
+                        print!("{}", text);
+                        Ok(())
+                    },
+                }
+            })
+        },
+        Command::Help => {
+            print!("{}", USAGE);
+            Ok(())
+        },
+        Command::Version => {
+            println!("snake-bench {}", env!("CARGO_PKG_VERSION"));
+            Ok(())
+        },
+    };
+
+    if let Err(e) = result {
+        eprintln!("snake-bench: {}", e);
+        process::exit(1);
+    }
+}
+
+/// Parses the command line `args` (without the program name).
+///
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Command, String> {
+    let mut config = BenchConfig::new(DEFAULT_HCELLS, DEFAULT_VCELLS);
+    let (mut hcells, mut vcells) = (DEFAULT_HCELLS, DEFAULT_VCELLS);
+    let mut level = None;
+    let mut max_ticks = None;
+    let mut sized = false;
+    let mut strategies = Vec::new();
+    let mut format = Format::Csv;
+    let mut output = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--strategy" => {
+                let name = args.next().ok_or("missing value for --strategy")?;
+                strategies.push(Strategy::from_name(&name)
+                    .ok_or_else(|| format!("invalid value '{}' for --strategy: expected greedy, astar or hamiltonian", name))?);
+            },
+            "--games" => {
+                config.games = parse_value(&arg, args.next())?;
+            },
+            "--seed" => {
+                config.seed = parse_value(&arg, args.next())?;
+            },
+            "--threads" => {
+                config.threads = parse_value(&arg, args.next())?;
+            },
+            "--width" => {
+                hcells = parse_value(&arg, args.next())?;
+                sized = true;
+            },
+            "--height" => {
+                vcells = parse_value(&arg, args.next())?;
+                sized = true;
+            },
+            "--wrap" => {
+                config.mode = BoardMode::WRAP;
+            },
+            "--level" => {
+                level = Some(Level::find(&args.next().ok_or("missing value for --level")?)?);
+            },
+            "--max-ticks" => {
+                max_ticks = Some(parse_value(&arg, args.next())?);
+            },
+            "--format" => {
+                format = match args.next().as_deref() {
+                    Some("csv")  => Format::Csv,
+                    Some("json") => Format::Json,
+                    Some(name) => return Err(format!("invalid value '{}' for --format: expected csv or json", name)),
+                    None => return Err(String::from("missing value for --format")),
+                };
+            },
+            "--output" => {
+                output = Some(args.next().ok_or("missing value for --output")?);
+            },
+            "-h" | "--help" => {
+                return Ok(Command::Help);
+            },
+            "-V" | "--version" => {
+                return Ok(Command::Version);
+            },
+            _ => {
+                return Err(format!("unknown option '{}'", arg));
+            },
+        }
+    }
+
+    if sized && level.is_some() {
+        return Err(String::from("--level sets the size of the board, so it cannot be used with --width or --height"));
+    }
+
+    config.level = match level {
+        Some(level) => level,
+        None if hcells < MIN_HCELLS || vcells < MIN_VCELLS => {
+            return Err(format!("The board must be at least {}x{} cells, but it is {}x{}",
+                               MIN_HCELLS, MIN_VCELLS, hcells, vcells));
+        },
+        None if hcells > MAX_HCELLS || vcells > MAX_VCELLS => {
+            return Err(format!("The board can be at most {}x{} cells, but it is {}x{}",
+                               MAX_HCELLS, MAX_VCELLS, hcells, vcells));
+        },
+        None => Level::open(hcells, vcells),
+    };
+
+    config.max_ticks = max_ticks
+        .unwrap_or_else(|| bench::default_max_ticks(config.level.hcells, config.level.vcells));
+
+    if !strategies.is_empty() {
+        config.strategies = strategies;
+    }
+
+    if config.games == 0 || config.threads == 0 {
+        return Err(String::from("--games and --threads must be at least 1"));
+    }
+
+    Ok(Command::Bench(config, format, output))
+}
@@ -0,0 +1,189 @@
+//! Campaigns: a series of `Level`s played one after the other. A level is cleared by reaching its
+//! target score, which moves on to the next one. Every collision costs a life, and the level is
+//! played again while there are lives left. The points scored on every attempt add up.
+//!
+//! The progress of a campaign is saved as plain text, so it can be resumed in a later session:
+//!
+//! ```text
+//! snake-rust-campaign 1
+//! stage 2
+//! lives 3
+//! score 27
+//! ```
+//!
+//! `stage` is the index of the level being played, starting from 0.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::level::Level;
+
+
+/// Version of the format progress is saved in.
+const FORMAT_VERSION: u32 = 1;
+const MAGIC: &str = "snake-rust-campaign";
+
+/// Lives at the start of a campaign.
+pub const START_LIVES: u32 = 3;
+
+
+/// A campaign being played.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Campaign {
+    levels      : Vec<Level>,
+    /// Index in `levels` of the level being played.
+    stage       : usize,
+    lives       : u32,
+    /// Points scored so far, on all the levels.
+    score       : u32,
+}
+
+
+/// What comes after an attempt at a level of a `Campaign`.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum CampaignStatus {
+    /// There is a level to play: either the next one or, after losing a life, the same one again.
+    PLAYING,
+    /// The last level has been cleared.
+    COMPLETED,
+    /// There are no lives left.
+    LOST,
+}
+
+
+impl Campaign {
+
+    /// Start a campaign through `levels`, in order, with `START_LIVES` lives.
+    pub fn new(levels: Vec<Level>) -> Campaign {
+        assert!(!levels.is_empty(), "a campaign needs at least one level");
+
+        Campaign {
+            levels,
+            stage   : 0,
+            lives   : START_LIVES,
+            score   : 0,
+        }
+    }
+
+    /// The level to play next.
+    pub fn level(&self) -> &Level {
+        &self.levels[self.stage]
+    }
+
+    /// Index of the level to play next, starting from 0.
+    pub fn stage(&self) -> usize {
+        self.stage
+    }
+
+    /// Number of levels in the campaign.
+    pub fn stages(&self) -> usize {
+        self.levels.len()
+    }
+
+    pub fn lives(&self) -> u32 {
+        self.lives
+    }
+
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    /// Whether `score` is enough to clear the current level.
+    pub fn target_reached(&self, score: u32) -> bool {
+        score >= self.level().target
+    }
+
+    /// Records an attempt at the current level that ended with `score`. The level is cleared if
+    /// the score reached its target; otherwise the `Snake` collided and a life is lost.
+    pub fn finish_level(&mut self, score: u32) -> CampaignStatus {
+        self.score += score;
+
+        if self.target_reached(score) {
+            if self.stage + 1 == self.levels.len() {
+                return CampaignStatus::COMPLETED;
+            }
+
+            self.stage += 1;
+            return CampaignStatus::PLAYING;
+        }
+
+        self.lives = self.lives.saturating_sub(1);
+
+        match self.lives {
+            0 => CampaignStatus::LOST,
+            _ => CampaignStatus::PLAYING,
+        }
+    }
+
+    /// Serializes the progress of the `Campaign` in the text format described at the top of this
+    /// module. The levels themselves are not saved.
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{} {}\n", MAGIC, FORMAT_VERSION);
+        text.push_str(&format!("stage {}\n", self.stage));
+        text.push_str(&format!("lives {}\n", self.lives));
+        text.push_str(&format!("score {}\n", self.score));
+        text
+    }
+
+    /// Resumes a campaign through `levels` from the progress saved in `text`.
+    pub fn parse(text: &str, levels: Vec<Level>) -> Result<Campaign, String> {
+        let mut lines = text.lines().enumerate().map(|(i, l)| (i + 1, l));
+
+        match lines.next().map(|(_, l)| l.split_whitespace().collect::<Vec<_>>()) {
+            Some(header) if header.len() == 2 && header[0] == MAGIC => {
+                if header[1] != FORMAT_VERSION.to_string() {
+                    return Err(format!("unsupported campaign format version {}", header[1]));
+                }
+            },
+            _ => return Err(String::from("not a campaign file")),
+        }
+
+        let mut campaign = Campaign::new(levels);
+
+        for (line, raw) in lines {
+            let fields = raw.split_whitespace().collect::<Vec<_>>();
+            let parse = |value: &str| value.parse::<u32>()
+                .map_err(|_| format!("line {}: invalid number '{}'", line, value));
+
+            match fields.as_slice() {
+                [] => {},
+                ["stage", value] => {
+                    campaign.stage = parse(value)? as usize;
+                },
+                ["lives", value] => {
+                    campaign.lives = parse(value)?;
+                },
+                ["score", value] => {
+                    campaign.score = parse(value)?;
+                },
+                _ => return Err(format!("line {}: unexpected '{}'", line, fields.join(" "))),
+            }
+        }
+
+        if campaign.stage >= campaign.levels.len() {
+            return Err(format!("there is no level {} in the campaign", campaign.stage + 1));
+        }
+
+        if campaign.lives == 0 {
+            return Err(String::from("the campaign has no lives left"));
+        }
+
+        Ok(campaign)
+    }
+
+    pub fn load(path: &Path, levels: Vec<Level>) -> Result<Campaign, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("could not read {}: {}", path.display(), e))?;
+
+        Campaign::parse(&text, levels).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        fs::write(path, self.to_text())
+    }
+}
@@ -0,0 +1,12 @@
+//! What the command lines of the game and of `snake-bench` have in common.
+
+use std::str::FromStr;
+
+
+/// Parses the `value` given to the option `name`, which is missing if there is none.
+pub fn parse_value<T: FromStr>(name: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("missing value for {}", name))?;
+
+    value.parse()
+        .map_err(|_| format!("invalid value '{}' for {}: expected a non-negative integer", value, name))
+}
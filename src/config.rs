@@ -0,0 +1,182 @@
+//! Settings chosen before the game starts, e.g. from the command line.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::ai::Strategy;
+use crate::level::Level;
+use crate::replay::Replay;
+use crate::sim::BoardMode;
+use crate::sim::versus::MAX_PLAYERS;
+
+
+/// Size of the board, in cells, unless configured otherwise.
+pub const DEFAULT_HCELLS    : u32 = 38;
+pub const DEFAULT_VCELLS    : u32 = 28;
+/// Size of a cell, in pixels, unless configured otherwise.
+pub const DEFAULT_CELL_SIZE : u32 = 20;
+/// Time between two ticks of the game, unless configured otherwise.
+pub const DEFAULT_SPEED     : Duration = Duration::from_millis(200);
+
+/// The `Snake` is created with 5 cells in the middle of the board, so the board must be at least
+/// this wide.
+pub const MIN_HCELLS        : u32 = 8;
+pub const MIN_VCELLS        : u32 = 2;
+pub const MIN_CELL_SIZE     : u32 = 2;
+pub const MIN_SPEED         : Duration = Duration::from_millis(10);
+
+/// Larger boards or cells would not fit on any screen, and would take too much memory to play on.
+pub const MAX_HCELLS        : u32 = 1000;
+pub const MAX_VCELLS        : u32 = 1000;
+pub const MAX_CELL_SIZE     : u32 = 100;
+
+
+/// How fast the game goes, relative to the configured speed.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Difficulty {
+    EASY,
+    NORMAL,
+    HARD,
+}
+
+
+impl Difficulty {
+
+    /// Every difficulty, from the easiest.
+    pub const ALL: [Difficulty; 3] = [Difficulty::EASY, Difficulty::NORMAL, Difficulty::HARD];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::EASY   => "Easy",
+            Difficulty::NORMAL => "Normal",
+            Difficulty::HARD   => "Hard",
+        }
+    }
+
+    /// Finds a difficulty by name, ignoring case.
+    pub fn from_name(name: &str) -> Option<Difficulty> {
+        Difficulty::ALL.into_iter().find(|d| d.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Time between two ticks at this difficulty, when `speed` is the one configured.
+    pub fn speed(self, speed: Duration) -> Duration {
+        match self {
+            Difficulty::EASY   => speed * 3 / 2,
+            Difficulty::NORMAL => speed,
+            Difficulty::HARD   => (speed / 2).max(MIN_SPEED),
+        }
+    }
+}
+
+
+/// Everything `run` needs to know to set up a `Game`.
+#[derive(Debug, Clone)]
+pub struct GameConfig {
+    /// Path of the font file used to render text. If `None`, the font is looked for in the usual
+    /// places, falling back to the one embedded into the game.
+    pub font_path   : Option<PathBuf>,
+    /// Seed used by every game. If `None`, each game picks a fresh random seed.
+    pub seed        : Option<u64>,
+    /// Width of the board, in cells.
+    pub hcells      : u32,
+    /// Height of the board, in cells.
+    pub vcells      : u32,
+    /// Size of every cell, in pixels. The window is sized to fit the board.
+    pub cell_size   : u32,
+    /// Time between two ticks of the game, when not boosting.
+    pub speed       : Duration,
+    /// Whether the edges of the board are walls or wrap around.
+    pub mode        : BoardMode,
+    /// Scales `speed` up or down.
+    pub difficulty  : Difficulty,
+    /// Number of snakes on the board. With more than one, new games are versus games.
+    pub players     : usize,
+    /// Number of computer-controlled snakes joining the human players, whose strategy depends
+    /// on `difficulty`. Any makes new games versus games.
+    pub opponents   : usize,
+    /// A built-in strategy that steers the first `Snake` instead of the player.
+    pub bot         : Option<Strategy>,
+    /// The level to play on. The board then has the size of the level. If `None`, the board is
+    /// open, without internal walls.
+    pub level       : Option<Level>,
+    /// A recorded game to play back instead of letting the user play. The board then has the
+    /// size and mode the replay was recorded with.
+    pub replay      : Option<Replay>,
+}
+
+
+impl GameConfig {
+
+    /// A configuration with the default board.
+    pub fn new() -> GameConfig {
+        GameConfig {
+            font_path   : None,
+            seed        : None,
+            hcells      : DEFAULT_HCELLS,
+            vcells      : DEFAULT_VCELLS,
+            cell_size   : DEFAULT_CELL_SIZE,
+            speed       : DEFAULT_SPEED,
+            mode        : BoardMode::WALLS,
+            difficulty  : Difficulty::NORMAL,
+            players     : 1,
+            opponents   : 0,
+            bot         : None,
+            level       : None,
+            replay      : None,
+        }
+    }
+
+    /// Checks the board can actually be played. A replay is checked against the board it was
+    /// recorded on. A level has already been checked to fit the `Snake`, so it may be smaller
+    /// than an open board.
+    pub fn validate(&self) -> Result<(), String> {
+        let (hcells, vcells, level) = match &self.replay {
+            Some(replay) => (replay.hcells, replay.vcells, replay.level.as_ref()),
+            None => (self.hcells, self.vcells, self.level.as_ref()),
+        };
+
+        if level.is_none() && (hcells < MIN_HCELLS || vcells < MIN_VCELLS) {
+            return Err(format!("The board must be at least {}x{} cells, but it is {}x{}",
+                               MIN_HCELLS, MIN_VCELLS, hcells, vcells));
+        }
+
+        if hcells > MAX_HCELLS || vcells > MAX_VCELLS {
+            return Err(format!("The board can be at most {}x{} cells, but it is {}x{}",
+                               MAX_HCELLS, MAX_VCELLS, hcells, vcells));
+        }
+
+        if self.cell_size < MIN_CELL_SIZE {
+            return Err(format!("The cells must be at least {} pixels wide, but they are {}",
+                               MIN_CELL_SIZE, self.cell_size));
+        }
+
+        if self.cell_size > MAX_CELL_SIZE {
+            return Err(format!("The cells can be at most {} pixels wide, but they are {}",
+                               MAX_CELL_SIZE, self.cell_size));
+        }
+
+        if self.speed < MIN_SPEED {
+            return Err(format!("The time between ticks must be at least {}ms, but it is {}ms",
+                               MIN_SPEED.as_millis(), self.speed.as_millis()));
+        }
+
+        if !(1..=MAX_PLAYERS).contains(&self.players) {
+            return Err(format!("There can be between 1 and {} players, but there are {}",
+                               MAX_PLAYERS, self.players));
+        }
+
+        if self.players + self.opponents > MAX_PLAYERS {
+            return Err(format!("There can be at most {} snakes, but there are {} players and {} computer snakes",
+                               MAX_PLAYERS, self.players, self.opponents));
+        }
+
+        Ok(())
+    }
+}
+
+
+impl Default for GameConfig {
+    fn default() -> GameConfig {
+        GameConfig::new()
+    }
+}
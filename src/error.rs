@@ -0,0 +1,45 @@
+//! Everything that can go wrong while running the game.
+
+use std::error::Error;
+use std::fmt;
+
+
+/// An error that stops the `Game`. Most of these come from SDL, which reports its errors as
+/// plain strings; we keep that message and tell which step failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnakeError {
+    /// SDL (or one of its subsystems) could not be initialized.
+    VideoInit(String),
+    /// The window, or the canvas to draw on it, could not be created.
+    Window(String),
+    /// The font could not be loaded.
+    FontLoad(String),
+    /// Something could not be drawn.
+    Render(String),
+    /// A texture could not be created.
+    Texture(String),
+    /// The `Game` FSM got a transition that is not possible in its current state.
+    InvalidTransition { state: String, transition: String },
+    /// The configuration does not describe a playable game.
+    Config(String),
+}
+
+
+impl fmt::Display for SnakeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnakeError::VideoInit(e) => write!(f, "could not initialize SDL: {}", e),
+            SnakeError::Window(e)    => write!(f, "could not create the window: {}", e),
+            SnakeError::FontLoad(e)  => write!(f, "could not load the font: {}", e),
+            SnakeError::Render(e)    => write!(f, "could not draw: {}", e),
+            SnakeError::Texture(e)   => write!(f, "could not create a texture: {}", e),
+            SnakeError::InvalidTransition { state, transition } => {
+                write!(f, "invalid transition {} in state {}", transition, state)
+            },
+            SnakeError::Config(e)    => write!(f, "invalid configuration: {}", e),
+        }
+    }
+}
+
+
+impl Error for SnakeError {}
@@ -0,0 +1,78 @@
+//! Finding the font used to render text. We look for Roboto in the places a user or a
+//! distribution would install it and, if it is nowhere to be found, fall back to the copy
+//! embedded into the binary. So the game runs wherever it is installed.
+
+use std::path::{Path, PathBuf};
+
+use crate::paths;
+
+
+/// Name of the font file we look for.
+const FONT_FILE: &str = "Roboto-Regular.ttf";
+
+/// The font shipped in `res/`, built into the binary.
+pub static EMBEDDED_FONT: &[u8] = include_bytes!("../res/Roboto-Regular.ttf");
+
+/// Where fonts live below a data directory, following the layouts used by fontconfig and the
+/// common distributions.
+const FONT_SUBDIRS: &[&str] = &[
+    "fonts/truetype/roboto/unhinted/RobotoTTF", // Debian, Ubuntu (`fonts-roboto`)
+    "fonts/truetype/roboto/unhinted",
+    "fonts/truetype/roboto/hinted",
+    "fonts/truetype/roboto",
+    "fonts/roboto",
+    "fonts/google-roboto",                      // Fedora
+    "fonts/TTF",                                // Arch
+    "fonts/truetype",
+    "fonts",
+];
+
+
+/// Where to load the font from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FontSource {
+    File(PathBuf),
+    Embedded,
+}
+
+
+/// Every directory where we look for the font, in order: the game's own config and data
+/// directories first, then the user's fonts and finally the system-wide ones.
+pub fn font_search_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    dirs.extend(paths::config_dir());
+    dirs.extend(paths::data_dir());
+
+    if let Some(data_home) = paths::data_home() {
+        dirs.extend(FONT_SUBDIRS.iter().map(|sub| data_home.join(sub)));
+    }
+
+    if let Some(home) = paths::home_dir() {
+        dirs.push(home.join(".fonts"));
+        dirs.push(home.join("Library/Fonts"));
+    }
+
+    for data_dir in paths::data_dirs() {
+        dirs.extend(FONT_SUBDIRS.iter().map(|sub| data_dir.join(sub)));
+    }
+
+    dirs.push(PathBuf::from("/Library/Fonts"));
+    dirs
+}
+
+
+/// Finds the font to use. An `explicit` path (e.g. given on the command line) always wins;
+/// otherwise the first font file found in `font_search_dirs` is used, and the embedded font if
+/// there is none.
+pub fn find_font(explicit: Option<&Path>) -> FontSource {
+    if let Some(path) = explicit {
+        return FontSource::File(path.to_path_buf());
+    }
+
+    font_search_dirs().into_iter()
+        .map(|dir| dir.join(FONT_FILE))
+        .find(|path| path.is_file())
+        .map(FontSource::File)
+        .unwrap_or(FontSource::Embedded)
+}
@@ -0,0 +1,2028 @@
+//! The SDL front-end: the window, the menus and the screens of the game. It merely renders what
+//! the `Simulation` holds, and turns the input of the players into turns.
+
+
+use std::fs;
+use std::io;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant};
+
+use sdl2::pixels::Color;
+use sdl2::controller::GameController;
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+use sdl2::rect::Rect;
+use sdl2::render::BlendMode;
+use sdl2::render;
+use sdl2::rwops::RWops;
+//use sdl2::render::Canvas;
+use sdl2::video::Window;
+use sdl2::event::WindowEvent;
+//use sdl2::EventPump;
+use sdl2::ttf;
+
+use crate::ai::{Controller, Strategy};
+use crate::campaign::{Campaign, CampaignStatus};
+use crate::config::{Difficulty, GameConfig};
+use crate::error::SnakeError;
+use crate::level::Level;
+use crate::sim::{BoardMode, Collision, Coordinate, Direction, Simulation, Snake, StepOutcome};
+use crate::sim::versus::{Match, RoundOutcome, Versus, MAX_PLAYERS, ROUNDS_TO_WIN};
+use crate::replay::{Playback, Replay};
+use crate::scores::{self, Board, HighScore, HighScores};
+use crate::fonts::{self, FontSource};
+use crate::input::TurnQueue;
+use crate::keymap::{Action, Context, Input, Keymap, Steering, Stick};
+use crate::menu::{Menu, MenuEvent, MenuId};
+use crate::paths;
+
+
+/// The window is never smaller than this, even for tiny boards. Otherwise there would be no room
+/// for the text.
+const MIN_WIDTH   : u32 = 400;
+const MIN_HEIGHT  : u32 = 300;
+/// Margin around the `GameArea`.
+const SPACING     : u32 = 20;
+
+/// While boosting, the game goes this many times faster.
+const BOOST_FACTOR: u32 = 4;
+
+/// The board modes, in the order the menu offers them.
+const MODES: [BoardMode; 2] = [BoardMode::WALLS, BoardMode::WRAP];
+
+/// For this long after the end of a game, input is ignored, so keys still pressed from the game
+/// don't pick a choice by accident.
+const INPUT_DELAY: Duration = Duration::from_millis(600);
+
+/// Name and colors (head, then body) of the snake of every player of a versus game. The first
+/// one is the colors of the only `Snake` of a single-player game.
+const PLAYER_COLORS: [(&str, Color, Color); 4] = [
+    ("Blue",   Color::RGB(0, 255, 0),   Color::RGB(0, 0, 255)),
+    ("Orange", Color::RGB(255, 160, 0), Color::RGB(220, 60, 0)),
+    ("Purple", Color::RGB(255, 0, 255), Color::RGB(130, 0, 170)),
+    ("Teal",   Color::RGB(0, 230, 230), Color::RGB(0, 130, 130)),
+];
+/// Color of the snakes that died in a round still going on.
+const DEAD_COLOR: Color = Color::RGB(170, 170, 170);
+
+/// Name of the file, inside the data directory, where the last game played is saved.
+const LAST_REPLAY : &str = "last.replay";
+/// Name of the file, inside the data directory, where the progress of the campaign is saved.
+const CAMPAIGN_PROGRESS : &str = "campaign.progress";
+/// Name of the file, inside the config directory, where the keymap is saved.
+const KEYMAP : &str = "keymap";
+/// Name of the file, inside the data directory, where the high scores are saved.
+const HIGH_SCORES : &str = "high-scores";
+
+
+/// Entry point. The `config` tells which font to use for rendering text, the size of the board
+/// and so on.
+///
+/// If the `config` holds a replay, it is played back instead of letting the user play.
+///
+pub fn run(config: &GameConfig) -> Result<(), SnakeError> {
+    let mut config = config.clone();
+    if let Some(level) = &config.level {
+        config.hcells = level.hcells;
+        config.vcells = level.vcells;
+    }
+
+    if let Some(replay) = &config.replay {
+        config.hcells = replay.hcells;
+        config.vcells = replay.vcells;
+        config.mode   = replay.mode;
+    }
+
+    config.validate().map_err(SnakeError::Config)?;
+
+    let sdl_context = sdl2::init().map_err(SnakeError::VideoInit)?;
+    let timer_subsystem = sdl_context.timer().map_err(SnakeError::VideoInit)?;
+    let ttf_context = ttf::init().map_err(|e| SnakeError::FontLoad(e.to_string()))?;
+    let mut game = Game::new(&sdl_context, &timer_subsystem, &ttf_context, &config)?;
+    game.start()
+}
+
+
+/// A particular `Game` can be in any of these states. We can think of they as different
+/// screens in the game.
+#[derive(Debug)]
+enum GameState {
+    STARTING,
+    PLAYING,
+    PAUSED,
+    /// Shown before each level of a campaign.
+    INTRO,
+    GAMEOVER,
+    WON,
+}
+
+
+/// In a given `GameState`, certain actions are possible. Those are denoted by these
+/// `GameTransition`s.
+#[derive(PartialEq, Debug)]
+enum GameTransition {
+    PLAY,
+    PAUSE,
+    LOSE,
+    WIN,
+    /// Start the same game over.
+    RESTART,
+    /// Back to the menu.
+    MENU,
+    EXIT,
+}
+
+
+/// The area through which the snake can move is composed of cells. The area has `hcells` width
+/// and `vcells` height, and each cell is `cell_size` pixels wide. The area is centered in a
+/// window of `width` x `height` pixels.
+struct GameArea {
+    width       : u32,
+    height      : u32,
+    hcells      : u32,
+    vcells      : u32,
+    cell_size   : u32,
+    game_area   : Rect,
+    /// SDL `Rect`angles conforming the game grid:
+    grid        : Vec<Rect>,
+}
+
+
+/// We use the GameContext to stash anything related to the underlying SDL structures.
+struct GameContext<'time> {
+    _timer          : sdl2::timer::Timer<'time, 'time>,
+    /// Interval (in milliseconds) between two ticks. It is shared with the timer callback,
+    /// which reads it every time it re-arms itself.
+    tick_interval   : Arc<AtomicU32>,
+    canvas          : sdl2::render::Canvas<Window>,
+    event_pump      : sdl2::EventPump,
+    controller_subsystem: sdl2::GameControllerSubsystem,
+    /// The game controllers plugged in, with the state of their left stick.
+    controllers     : Vec<(GameController, Stick)>,
+    current_state   : GameState,
+}
+
+
+impl<'time> GameContext<'time> {
+
+    /// Constructor
+    fn new(
+        sdl_context: &'time sdl2::Sdl, timer_subsystem: &'time sdl2::TimerSubsystem,
+        display: &GameArea, speed: Duration,
+    ) -> Result<GameContext<'time>, SnakeError>
+    {
+        let video_subsystem = sdl_context.video().map_err(SnakeError::VideoInit)?;
+
+        let window = video_subsystem.window("Simple Snake", display.width, display.height)
+            .position_centered()
+            .opengl()
+            .build()
+            .map_err(|e| SnakeError::Window(e.to_string()))?;
+
+        let mut canvas = window.into_canvas().build()
+            .map_err(|e| SnakeError::Window(e.to_string()))?;
+
+        canvas.set_draw_color(Color::RGB(0, 255, 255));
+        canvas.clear();
+
+        let event_pump    = sdl_context.event_pump().map_err(SnakeError::VideoInit)?;
+        // Controllers already plugged in are reported with the same events as those plugged in
+        // later, so they are all opened as those events come.
+        let controller_subsystem = sdl_context.game_controller().map_err(SnakeError::VideoInit)?;
+        let event_manager = sdl_context.event().map_err(SnakeError::VideoInit)?;
+
+        event_manager.register_custom_event::<TimerEvent>().map_err(SnakeError::VideoInit)?;
+
+        // `EventSender` objects can be moved to other threads and allow pushing
+        // events to the queue from there:
+        let event_sender = event_manager.event_sender();
+
+        struct TimerEvent{} // No payload to carry.
+
+        let tick_interval = Arc::new(AtomicU32::new(duration_to_millis(speed)));
+        let timer_interval = Arc::clone(&tick_interval);
+
+        // Set a timer callback that pushes `TimerEvent` events.
+        let _timer = timer_subsystem.add_timer(
+            tick_interval.load(Ordering::Relaxed),
+            Box::new(move || -> u32 {
+                // Queue next timer event. Note that there is no need to pause the timer,
+                // since if an event of this same type is in the queue, the push operation is a no-op.
+                // If the queue is full, this tick is simply skipped.
+                let _ = event_sender.push_custom_event( TimerEvent{} );
+                timer_interval.load(Ordering::Relaxed) // Return new interval.
+            }
+        ));
+
+        Ok(GameContext {
+            _timer,
+            tick_interval,
+            current_state : GameState::STARTING,
+            canvas        : canvas,
+            event_pump    : event_pump,
+            controller_subsystem,
+            controllers   : Vec::new(),
+        })
+    }
+
+    /// Opens the controller at `index` among the joysticks, so its events are reported.
+    fn open_controller(&mut self, index: u32) {
+        match self.controller_subsystem.open(index) {
+            Ok(controller) => {
+                let id = controller.instance_id();
+                if !self.controllers.iter().any(|(c, _)| c.instance_id() == id) {
+                    self.controllers.push((controller, Stick::new()));
+                }
+            },
+            Err(e) => eprintln!("Could not open controller {}: {}", index, e),
+        }
+    }
+
+    /// Waits for the next event. Controllers plugged in or out are handled here, and moves of
+    /// their left stick become presses of the D-pad, so the screens only deal with buttons.
+    fn wait_event(&mut self) -> Event {
+        loop {
+            match self.event_pump.wait_event() {
+                Event::ControllerDeviceAdded { which, .. } => {
+                    self.open_controller(which);
+                },
+                Event::ControllerDeviceRemoved { which, .. } => {
+                    self.controllers.retain(|(c, _)| c.instance_id() != which);
+                },
+                Event::ControllerAxisMotion { timestamp, which, axis, value } => {
+                    let pressed = self.controllers.iter_mut()
+                        .find(|(c, _)| c.instance_id() == which)
+                        .and_then(|(_, stick)| stick.move_axis(axis, value));
+
+                    if let Some(button) = pressed {
+                        return Event::ControllerButtonDown { timestamp, which, button };
+                    }
+                },
+                event => return event,
+            }
+        }
+    }
+}
+
+
+/// Loads the keymap saved in the config directory. If there is none, or it cannot be read, the
+/// default keys are used. Conflicting bindings are reported, since some keys won't work.
+fn load_keymap() -> Keymap {
+    let path = match paths::config_dir() {
+        Some(dir) => dir.join(KEYMAP),
+        None => return Keymap::new(),
+    };
+
+    if !path.exists() {
+        return Keymap::new();
+    }
+
+    let keymap = Keymap::load(&path).unwrap_or_else(|e| {
+        eprintln!("Could not read the keymap: {}", e);
+        Keymap::new()
+    });
+
+    for conflict in keymap.conflicts() {
+        eprintln!("{}: {} is bound to both '{}' and '{}'", path.display(),
+                  conflict.input.name(), conflict.first.name(), conflict.second.name());
+    }
+
+    keymap
+}
+
+
+/// How `mode` is called in the menus.
+fn mode_label(mode: BoardMode) -> &'static str {
+    match mode {
+        BoardMode::WALLS => "Walls",
+        BoardMode::WRAP  => "Wrap around",
+    }
+}
+
+
+/// Converts `duration` into the milliseconds expected by the SDL timer. A zero interval would
+/// cancel the timer, so we never go below 1ms.
+fn duration_to_millis(duration: Duration) -> u32 {
+    duration.as_millis().clamp(1, u32::MAX as u128) as u32
+}
+
+
+/// Generates the SDL `Rect`angle corresponding to the given game `coord`inate.
+/// The `Rect` must fit inside `GameArea`; otherwise `None` is returned.
+fn create_rect(display: &GameArea, coord: &Coordinate) -> Option<Rect> {
+
+    if coord.x > display.hcells {
+        return None;
+    }
+
+    if coord.y > display.vcells {
+        return None;
+    }
+
+    let r = Rect::new(display.game_area.x() + (coord.x * display.cell_size) as i32,
+                      display.game_area.y() + (coord.y * display.cell_size) as i32,
+                      display.cell_size,
+                      display.cell_size);
+
+    return Some(r);
+}
+
+
+/// As explained earlier, GameArea is a grid of cells. Here we create `hcells` x `vcells` such
+/// cells as rectangles of `cell_size` pixels. The window is sized to fit them.
+fn create_grid(hcells: u32, vcells: u32, cell_size: u32) -> GameArea {
+    let area_width  = hcells * cell_size;
+    let area_height = vcells * cell_size;
+    let width       = MIN_WIDTH.max(area_width + 2 * SPACING);
+    let height      = MIN_HEIGHT.max(area_height + 2 * SPACING);
+
+    let mut display = GameArea {
+        width,
+        height,
+        vcells,
+        hcells,
+        cell_size,
+        game_area: Rect::new(((width - area_width) / 2) as i32,
+                             ((height - area_height) / 2) as i32,
+                             area_width,
+                             area_height),
+        grid: Vec::new(),
+    };
+
+    for vcell in 0..display.vcells {
+        for hcell in 0..display.hcells {
+            let r = create_rect(&display, &Coordinate { x: hcell, y: vcell }).unwrap();
+            display.grid.push(r);
+        }
+    }
+
+    return display;
+}
+
+
+/// Renders `snake` with its head and body in the given colors.
+fn render_snake(canvas: &mut render::Canvas<Window>, display: &GameArea, snake: &Snake, head: Color, body: Color) -> Result<(), SnakeError> {
+    canvas.set_draw_color(head);
+    canvas.fill_rect(create_rect(display, &snake.body[0])).map_err(SnakeError::Render)?;
+    canvas.set_draw_color(body);
+    for b in &snake.body[1..] {
+        canvas.fill_rect(create_rect(display, b)).map_err(SnakeError::Render)?;
+    }
+
+    Ok(())
+}
+
+
+/// Where the score is shown: right above the `GameArea`.
+fn create_score_rect(display: &GameArea) -> Rect {
+    Rect::new(display.game_area.x(), display.game_area.y() - SPACING as i32, 100, SPACING)
+}
+
+
+/// The actual state of the game.
+struct Game<'ttf> {
+    context     : GameContext<'ttf>,
+    display     : GameArea,
+    speed       : Duration,
+    /// Time between two ticks when not boosting.
+    normal_speed: Duration,
+    /// Time between two ticks chosen in the `GameConfig`, which the difficulty scales.
+    base_speed  : Duration,
+    difficulty  : Difficulty,
+    /// Whether the grid of cells is drawn.
+    show_grid   : bool,
+    /// The keys bound to every `Action`.
+    keymap      : Keymap,
+    /// Seed requested by the user for every game, if any.
+    seed        : Option<u64>,
+    /// Whether the edges of the `GameArea` are walls, for the next game.
+    mode        : BoardMode,
+    /// The levels that can be chosen from the menu. The first one is the open board.
+    levels      : Vec<Level>,
+    /// Index in `levels` of the level for the next game.
+    level_index : usize,
+    /// The campaign being played, if any. While in the menu, the campaign that can be resumed.
+    campaign    : Option<Campaign>,
+    sim         : Simulation,
+    /// Turns requested by the player, to be applied on the next ticks.
+    turns       : TurnQueue,
+    /// Number of snakes steered by people in the next game.
+    players     : usize,
+    /// Number of snakes steered by the computer in the next game. With more than one `Snake` in
+    /// all, it is a versus game.
+    opponents   : usize,
+    /// The round being played, in a versus game. While set, it is played instead of `sim`.
+    versus      : Option<Versus>,
+    /// What drives every `Snake` of a versus game: the players first, then the computer. In a
+    /// single-player game, the bot playing, if any.
+    drivers     : Vec<Box<dyn Controller>>,
+    /// The built-in strategy steering the first `Snake` instead of the player, if any.
+    bot         : Option<Strategy>,
+    /// The rounds won so far in the versus match.
+    scoreboard  : Match,
+    /// Recording of the game being played.
+    replay      : Replay,
+    /// When playing a replay back, it feeds the inputs instead of the keyboard.
+    playback    : Option<Playback>,
+    /// Area covered by each entry of the menu last drawn.
+    menu_rects  : Vec<Rect>,
+    /// Time spent in the `PLAYING` state during the current game.
+    play_time   : Duration,
+    /// Name the player entered for the last high score, offered again for the next one.
+    player_name : String,
+
+    score_rect  : Rect,
+    font        : ttf::Font<'ttf, 'ttf>,
+}
+
+
+impl<'ttf> Game<'ttf> {
+
+    /// Create a new `Game`. Once a game is created, a game can be `start`ed(). As part of creating
+    /// the `Game`, its `GameContext` is also initialized. Such initialization consists of setting
+    /// up everything related to SDL2.
+
+    fn new(
+        sdl_context: &'ttf sdl2::Sdl, timer_subsystem: &'ttf sdl2::TimerSubsystem,
+        ttf_context: &'ttf ttf::Sdl2TtfContext, config: &GameConfig,
+    ) -> Result<Game<'ttf>, SnakeError>
+    {
+        let mut font = match fonts::find_font(config.font_path.as_deref()) {
+            FontSource::File(path) => {
+                ttf_context.load_font(&path, 24)
+                    .map_err(|e| SnakeError::FontLoad(format!("{}: {}", path.display(), e)))?
+            },
+            FontSource::Embedded => {
+                let rwops = RWops::from_bytes(fonts::EMBEDDED_FONT).map_err(SnakeError::FontLoad)?;
+                ttf_context.load_font_from_rwops(rwops, 24).map_err(SnakeError::FontLoad)?
+            },
+        };
+
+        font.set_style(ttf::FontStyle::BOLD);
+
+        let display = create_grid(config.hcells, config.vcells, config.cell_size);
+        let mut ctxt = GameContext::new(sdl_context, timer_subsystem, &display, config.speed)?;
+
+        // The level given in the `config` is preselected in the menu, next to the bundled ones.
+        let mut levels = vec![Level::open(config.hcells, config.vcells)];
+        levels.extend(Level::bundled());
+
+        let level_index = match &config.level {
+            Some(level) => {
+                levels.iter().position(|l| l == level).unwrap_or_else(|| {
+                    levels.insert(1, level.clone());
+                    1
+                })
+            },
+            None => 0,
+        };
+
+        let sim = match &config.replay {
+            Some(replay) => {
+                // A replay goes straight to the game.
+                ctxt.current_state = GameState::PLAYING;
+                replay.simulation().map_err(SnakeError::Config)?
+            },
+            None => Simulation::from_level(levels[level_index].clone(), config.mode,
+                                           config.seed.unwrap_or_else(rand::random))
+                .map_err(SnakeError::Config)?,
+        };
+
+        let score_rect = create_score_rect(&display);
+
+        let game = Game {
+            context : ctxt,
+            display : display,
+            speed   : config.speed,
+            normal_speed : config.difficulty.speed(config.speed),
+            base_speed  : config.speed,
+            difficulty  : config.difficulty,
+            show_grid   : false,
+            keymap      : load_keymap(),
+            seed    : config.seed,
+            mode    : config.mode,
+            levels,
+            level_index,
+            campaign: None,
+            replay  : Replay::for_simulation(&sim),
+            playback: config.replay.clone().map(Playback::new),
+            menu_rects  : Vec::new(),
+            play_time   : Duration::ZERO,
+            player_name : std::env::var("USER").unwrap_or_default(),
+            sim     : sim,
+            turns   : TurnQueue::new(),
+            players : config.players,
+            opponents   : config.opponents,
+            versus  : None,
+            drivers : Vec::new(),
+            bot     : config.bot,
+            scoreboard  : Match::new(config.players + config.opponents),
+            score_rect : score_rect,
+            font    : font,
+        };
+
+        Ok(game)
+    }
+
+    /// Gets the `Game` ready for a new round on the selected level, or the current level of the
+    /// campaign: the `Simulation` starts over and any leftover input or speed boost from the
+    /// previous round is discarded. With several snakes, outside of a campaign, the round is a
+    /// versus one.
+    fn reset(&mut self) -> Result<(), SnakeError> {
+        let level = match &self.campaign {
+            Some(campaign) => campaign.level().clone(),
+            None => self.levels[self.level_index].clone(),
+        };
+        self.resize_display(level.hcells, level.vcells)?;
+
+        self.versus = None;
+        if self.snakes() > 1 && self.campaign.is_none() {
+            self.versus = Some(self.new_versus(&level)?);
+
+            let strategy = Strategy::for_difficulty(self.difficulty);
+            self.drivers = (0..self.snakes())
+                .map(|i| match (i, self.bot) {
+                    (0, Some(bot)) => bot.controller(),
+                    _ if i < self.players => Box::new(TurnQueue::new()),
+                    _ => strategy.controller(),
+                })
+                .collect();
+
+            if self.scoreboard.players() != self.snakes() {
+                self.scoreboard = Match::new(self.snakes());
+            }
+        }
+        else {
+            self.drivers = self.bot.map(Strategy::controller).into_iter().collect();
+        }
+
+        self.sim = Simulation::from_level(level, self.mode, self.seed.unwrap_or_else(rand::random))
+            .map_err(SnakeError::Config)?;
+        self.replay = Replay::for_simulation(&self.sim);
+        self.play_time = Duration::ZERO;
+        self.turns.clear();
+        self.normal_speed = self.difficulty.speed(self.base_speed);
+        self.set_speed(self.normal_speed);
+
+        Ok(())
+    }
+
+    /// Number of snakes in the next game: the players, and as many computer snakes as fit.
+    fn snakes(&self) -> usize {
+        (self.players + self.opponents).min(MAX_PLAYERS)
+    }
+
+    /// A versus round on `level` or, if the walls of the level leave no room for every `Snake`,
+    /// on an open board of the same size.
+    fn new_versus(&self, level: &Level) -> Result<Versus, SnakeError> {
+        let seed = self.seed.unwrap_or_else(rand::random);
+
+        Versus::new(level.clone(), self.mode, self.snakes(), seed).or_else(|e| {
+            eprintln!("{}; playing on an open board instead", e);
+            Versus::new(Level::open(level.hcells, level.vcells), self.mode, self.snakes(), seed)
+                .map_err(SnakeError::Config)
+        })
+    }
+
+    /// Makes the `GameArea` `hcells` x `vcells` cells, resizing the window to fit it.
+    fn resize_display(&mut self, hcells: u32, vcells: u32) -> Result<(), SnakeError> {
+        if (hcells, vcells) == (self.display.hcells, self.display.vcells) {
+            return Ok(());
+        }
+
+        self.display = create_grid(hcells, vcells, self.display.cell_size);
+        self.score_rect = create_score_rect(&self.display);
+
+        let window = self.context.canvas.window_mut();
+        window.set_size(self.display.width, self.display.height)
+            .map_err(|e| SnakeError::Window(e.to_string()))?;
+        window.set_position(sdl2::video::WindowPos::Centered, sdl2::video::WindowPos::Centered);
+
+        Ok(())
+    }
+
+    /// Changes the interval between two ticks of the game. The timer picks up the new `speed`
+    /// the next time it fires.
+    fn set_speed(&mut self, speed: Duration) {
+        self.speed = speed;
+        self.context.tick_interval.store(duration_to_millis(speed), Ordering::Relaxed);
+    }
+
+    /// The menu shown in the `STARTING` state, reflecting the current settings.
+    fn build_menu(&self) -> Menu {
+        let campaign = match &self.campaign {
+            Some(campaign) => format!("Continue Campaign: level {} of {}", campaign.stage() + 1, campaign.stages()),
+            None => String::from("Campaign"),
+        };
+
+        let modes = MODES.iter().map(|m| String::from(mode_label(*m))).collect();
+        let mode = MODES.iter().position(|m| *m == self.mode).unwrap_or(0);
+        let difficulties = Difficulty::ALL.iter().map(|d| String::from(d.name())).collect();
+        let difficulty = Difficulty::ALL.iter().position(|d| *d == self.difficulty).unwrap_or(0);
+        let players = (1..=PLAYER_COLORS.len()).map(|n| n.to_string()).collect();
+        let opponents = (0..PLAYER_COLORS.len()).map(|n| n.to_string()).collect();
+        let levels = self.levels.iter().map(|l| l.name.clone()).collect();
+        let grid = vec![String::from("Off"), String::from("On")];
+
+        let mut menu = Menu::new("Simple Snake");
+        menu.action("New Game", MenuId::NEWGAME)
+            .action(&campaign, MenuId::CAMPAIGN)
+            .selector("Mode", MenuId::MODE, modes, mode)
+            .selector("Difficulty", MenuId::DIFFICULTY, difficulties, difficulty)
+            .selector("Players", MenuId::PLAYERS, players, self.players - 1)
+            .selector("Computer snakes", MenuId::OPPONENTS, opponents, self.opponents)
+            .action("High Scores", MenuId::HIGHSCORES)
+            .submenu("Settings")
+                .selector("Level", MenuId::LEVEL, levels, self.level_index)
+                .selector("Grid", MenuId::GRID, grid, self.show_grid as usize)
+            .end_submenu()
+            .action("Controls", MenuId::CONTROLS)
+            .action("Exit", MenuId::EXIT);
+
+        menu
+    }
+
+    /// Renders the page of `menu` shown, vertically centered on `center`: its title, and below it
+    /// the entries, highlighting the current one. With `panel`, they are drawn on a white box, so
+    /// the menu can go over the `GameArea`. The area covered by every entry is kept in
+    /// `menu_rects`, to find out which one the mouse is on.
+    fn render_menu(&mut self, texture_creator: &render::TextureCreator<sdl2::video::WindowContext>, menu: &Menu, center: i32, panel: bool) -> Result<(), SnakeError> {
+        let options : Vec<String> = menu.labels().iter().enumerate()
+            .map(|(i, option)| {
+                let marker = if i == menu.current() { ">" } else { " " };
+                format!("{} {}", marker, option)
+            })
+            .collect();
+
+        // All the options are aligned to the left, so the menu is centered on the widest one.
+        let mut widest = 0;
+        for option in &options {
+            let (fw, _) = self.font.size_of(option).map_err(|e| SnakeError::Render(e.to_string()))?;
+            widest = widest.max(fw);
+        }
+
+        let width = self.display.width as i32;
+        // The title takes the place of two entries.
+        let top = center - (options.len() as i32 + 2) * SPACING as i32;
+
+        let (tw, th) = self.font.size_of(menu.title()).map_err(|e| SnakeError::Render(e.to_string()))?;
+
+        if panel {
+            let panel_width = widest.max(tw) + 2 * SPACING;
+            let panel_height = (options.len() as u32 + 2) * 2 * SPACING + SPACING;
+            let panel_rect = Rect::new(width/2 - panel_width as i32/2, top - SPACING as i32/2,
+                                       panel_width, panel_height);
+
+            self.context.canvas.set_draw_color(Color::RGB(255, 255, 255));
+            self.context.canvas.fill_rect(panel_rect).map_err(SnakeError::Render)?;
+            self.context.canvas.set_draw_color(Color::RGB(255, 0, 0));
+            self.context.canvas.draw_rect(panel_rect).map_err(SnakeError::Render)?;
+        }
+        let title_surface = self.font
+            .render(menu.title())
+            .solid(Color::RGB(255, 0, 0))
+            .map_err(|e| SnakeError::Render(e.to_string()))?;
+        let title_texture = texture_creator
+            .create_texture_from_surface(&title_surface)
+            .map_err(|e| SnakeError::Texture(e.to_string()))?;
+        let title_rect = Rect::new(width/2 - tw as i32/2, top + SPACING as i32 - th as i32/2, tw, th);
+        self.context.canvas.copy(&title_texture, None, Some(title_rect))
+            .map_err(SnakeError::Render)?;
+
+        let top = top + 4 * SPACING as i32;
+        self.menu_rects.clear();
+
+        for (i, option) in options.iter().enumerate() {
+            let (fw, fh) = self.font.size_of(option).map_err(|e| SnakeError::Render(e.to_string()))?;
+
+            let option_surface  = self.font
+                .render(option)
+                .solid(Color::RGB(0, 0, 0))
+                .map_err(|e| SnakeError::Render(e.to_string()))?;
+            let option_texture = texture_creator
+                .create_texture_from_surface(&option_surface)
+                .map_err(|e| SnakeError::Texture(e.to_string()))?;
+            let option_rect = Rect::new(width/2 - widest as i32/2,
+                                        top + (2 * i as i32 + 1) * SPACING as i32 - fh as i32/2,
+                                        fw, fh);
+            self.context.canvas.copy(&option_texture, None, Some(option_rect))
+                .map_err(SnakeError::Render)?;
+
+            self.menu_rects.push(Rect::new(width/2 - widest as i32/2, top + 2 * i as i32 * SPACING as i32,
+                                           widest, 2 * SPACING));
+        }
+
+        Ok(())
+    }
+
+
+    /// Draws the page of `menu` shown.
+    fn draw_menu(&mut self, menu: &Menu) -> Result<(), SnakeError> {
+        // FIXME: Should `texture_creator` be a field?
+        let texture_creator = self.context.canvas.texture_creator();
+        self.context.canvas.set_draw_color(Color::RGB(255, 255, 255));
+        self.context.canvas.clear();
+        self.context.canvas.set_draw_color(Color::RGB(255, 0, 0));
+        self.context.canvas.draw_rect(self.display.game_area).map_err(SnakeError::Render)?;
+        self.render_menu(&texture_creator, menu, self.display.height as i32/2, false)?;
+        self.context.canvas.present();
+
+        Ok(())
+    }
+
+
+    /// Index of the entry of the menu last drawn at (`x`, `y`), if any.
+    fn menu_entry_at(&self, x: i32, y: i32) -> Option<usize> {
+        self.menu_rects.iter().position(|r| r.contains_point((x, y)))
+    }
+
+
+    /// The `Action` the key or button pressed in `event` is bound to in `context`, if any.
+    fn key_action(&self, event: &Event, context: Context) -> Option<Action> {
+        Input::pressed(event).and_then(|input| self.keymap.action(input, context))
+    }
+
+
+    /// Turns the keys and mouse clicks of the player into events of `menu`. Events that have
+    /// nothing to do with menus are returned as they are, for the caller to handle.
+    fn menu_input(&mut self, menu: &mut Menu, event: Event) -> Result<MenuEvent, Event> {
+        if let Some(action) = self.key_action(&event, Context::Menu) {
+            return match action {
+                Action::TurnUp => {
+                    menu.up();
+                    Ok(MenuEvent::NONE)
+                },
+                Action::TurnDown => {
+                    menu.down();
+                    Ok(MenuEvent::NONE)
+                },
+                Action::TurnLeft  => Ok(menu.change(-1)),
+                Action::TurnRight => Ok(menu.change(1)),
+                Action::Confirm   => Ok(menu.select()),
+                Action::Back      => Ok(menu.back_out()),
+                _ => Err(event),
+            };
+        }
+
+        match event {
+            Event::MouseMotion { x, y, .. } => {
+                if let Some(i) = self.menu_entry_at(x, y) {
+                    menu.hover(i);
+                }
+                Ok(MenuEvent::NONE)
+            },
+            Event::MouseButtonDown { mouse_btn: MouseButton::Left, x, y, .. } => {
+                match self.menu_entry_at(x, y) {
+                    Some(i) if menu.hover(i) => Ok(menu.select()),
+                    _ => Ok(MenuEvent::NONE),
+                }
+            },
+            Event::MouseButtonDown { mouse_btn: MouseButton::Right, x, y, .. } => {
+                match self.menu_entry_at(x, y) {
+                    Some(i) if menu.hover(i) => Ok(menu.change(-1)),
+                    _ => Ok(MenuEvent::NONE),
+                }
+            },
+            event => Err(event),
+        }
+    }
+
+
+    /// Applies the `value` picked for the setting `id` in the menu.
+    fn apply_setting(&mut self, id: MenuId, value: usize) {
+        match id {
+            MenuId::MODE       => self.mode = MODES[value],
+            MenuId::DIFFICULTY => self.difficulty = Difficulty::ALL[value],
+            MenuId::PLAYERS    => self.players = value + 1,
+            MenuId::OPPONENTS  => self.opponents = value,
+            MenuId::LEVEL      => self.level_index = value,
+            MenuId::GRID       => self.show_grid = value == 1,
+            _ => {}
+        }
+    }
+
+
+    /// Shows and manages the menu screen
+    fn game_starting(&mut self) -> Result<GameTransition, SnakeError> {
+
+        self.campaign = self.load_campaign();
+
+        let mut menu = self.build_menu();
+        self.draw_menu(&menu)?;
+
+        loop {
+            let event = self.context.wait_event();
+            if let Event::Quit {..} = event {
+                return Ok(GameTransition::EXIT);
+            }
+
+            let menu_event = match self.menu_input(&mut menu, event) {
+                Ok(menu_event) => menu_event,
+                Err(_) => continue,
+            };
+
+            match menu_event {
+                MenuEvent::SELECTED(MenuId::NEWGAME) => {
+                    self.campaign = None;
+                    self.scoreboard = Match::new(self.snakes());
+                    return Ok(GameTransition::PLAY);
+                },
+                MenuEvent::SELECTED(MenuId::CAMPAIGN) => {
+                    self.campaign.get_or_insert_with(|| Campaign::new(Level::bundled()));
+                    return Ok(GameTransition::PLAY);
+                },
+                MenuEvent::SELECTED(MenuId::HIGHSCORES) => {
+                    if self.high_scores_loop()? == GameTransition::EXIT {
+                        return Ok(GameTransition::EXIT);
+                    }
+                },
+                MenuEvent::SELECTED(MenuId::CONTROLS) => {
+                    if self.controls_loop()? == GameTransition::EXIT {
+                        return Ok(GameTransition::EXIT);
+                    }
+                },
+                MenuEvent::SELECTED(MenuId::EXIT) | MenuEvent::CLOSED => {
+                    return Ok(GameTransition::EXIT);
+                },
+                MenuEvent::CHANGED(id, value) => {
+                    self.apply_setting(id, value);
+                },
+                _ => {}
+            }
+
+            self.draw_menu(&menu)?;
+        }
+    }
+
+
+    /// The controls screen: every `Action` with its keys and buttons. An input bound to an action
+    /// that already uses it is flagged, since it won't work for both.
+    fn build_controls_menu(&self) -> Menu {
+        let conflicts = self.keymap.conflicts();
+
+        let mut menu = Menu::new("Controls");
+        for action in Action::ALL {
+            let inputs: Vec<String> = self.keymap.inputs(action).iter().map(|i| i.name()).collect();
+            let mut label = match inputs.is_empty() {
+                true  => format!("{}: (none)", action.label()),
+                false => format!("{}: {}", action.label(), inputs.join(", ")),
+            };
+
+            if let Some(conflict) = conflicts.iter().find(|c| c.second == action) {
+                label.push_str(&format!("  (taken by {})", conflict.first.label()));
+            }
+
+            menu.action(&label, MenuId::BIND(action));
+        }
+
+        menu.action("Reset to defaults", MenuId::DEFAULTS)
+            .back();
+
+        menu
+    }
+
+    /// Saves the keymap in the config directory.
+    fn save_keymap(&self) {
+        if let Some(dir) = paths::config_dir() {
+            if let Err(e) = self.keymap.save(&dir.join(KEYMAP)) {
+                eprintln!("Could not save the keymap: {}", e);
+            }
+        }
+    }
+
+    /// Asks the player for the new key or button of `action`, and returns the event that
+    /// answered: the input pressed, or the window being closed.
+    fn read_key(&mut self, action: Action) -> Result<Event, SnakeError> {
+        let prompt = format!("Press the new key or button to {}", action.label().to_lowercase());
+        self.draw_message(&[&prompt, "Escape to cancel"])?;
+
+        loop {
+            let event = self.context.wait_event();
+            if let Event::Quit {..} | Event::KeyDown { keycode: Some(_), repeat: false, .. } |
+                   Event::ControllerButtonDown {..} = event {
+                return Ok(event);
+            }
+        }
+    }
+
+    /// Shows the keys and buttons bound to every `Action`, and lets the player pick new ones.
+    /// Changes are saved right away.
+    fn controls_loop(&mut self) -> Result<GameTransition, SnakeError> {
+        let mut menu = self.build_controls_menu();
+        self.draw_menu(&menu)?;
+
+        loop {
+            let event = self.context.wait_event();
+            if let Event::Quit {..} = event {
+                return Ok(GameTransition::EXIT);
+            }
+
+            let menu_event = match self.menu_input(&mut menu, event) {
+                Ok(menu_event) => menu_event,
+                Err(_) => continue,
+            };
+
+            match menu_event {
+                MenuEvent::SELECTED(MenuId::BIND(action)) => {
+                    match self.read_key(action)? {
+                        Event::Quit {..} => return Ok(GameTransition::EXIT),
+                        Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {},
+                        event => {
+                            if let Some(input) = Input::pressed(&event) {
+                                self.keymap.rebind(action, input);
+                                self.save_keymap();
+                            }
+                        },
+                    }
+                },
+                MenuEvent::SELECTED(MenuId::DEFAULTS) => {
+                    self.keymap = Keymap::new();
+                    self.save_keymap();
+                },
+                MenuEvent::CLOSED => {
+                    return Ok(GameTransition::PLAY);
+                },
+                _ => {}
+            }
+
+            // The labels show the keys, which may have changed.
+            let current = menu.current();
+            menu = self.build_controls_menu();
+            menu.hover(current);
+
+            self.draw_menu(&menu)?;
+        }
+    }
+
+
+    /// Loads the high scores saved in the data directory. If there are none yet, or they cannot
+    /// be read, the tables start empty.
+    fn load_high_scores(&self) -> HighScores {
+        let path = match paths::data_dir() {
+            Some(dir) => dir.join(HIGH_SCORES),
+            None => return HighScores::new(),
+        };
+
+        if !path.exists() {
+            return HighScores::new();
+        }
+
+        HighScores::load(&path).unwrap_or_else(|e| {
+            eprintln!("Could not read the high scores: {}", e);
+            HighScores::new()
+        })
+    }
+
+    /// Shows the high scores of the board selected in the menu.
+    fn high_scores_loop(&mut self) -> Result<GameTransition, SnakeError> {
+        let level = &self.levels[self.level_index];
+        let board = Board {
+            mode    : self.mode,
+            hcells  : level.hcells,
+            vcells  : level.vcells,
+            level   : level.name.clone(),
+        };
+
+        let mut lines = vec![format!("High Scores: {}, {}", board.level, mode_label(board.mode))];
+        let scores = self.load_high_scores();
+        let table = scores.table(&board);
+
+        if table.is_empty() {
+            lines.push(String::from("No games yet"));
+        }
+
+        for (i, entry) in table.iter().enumerate() {
+            lines.push(format!("{}. {}  {}  (length {}, {}, {})", i + 1, entry.name, entry.score, entry.length,
+                               scores::format_duration(entry.duration), scores::format_date(entry.date)));
+        }
+
+        lines.push(String::from("Press any key to continue..."));
+
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        self.draw_message(&lines)?;
+        self.wait_any_key()
+    }
+
+    /// Lets the player type a name for the high score `score` just made. Returns `None` if the
+    /// player would rather not enter it, with Escape.
+    fn enter_name(&mut self, score: u32) -> Result<Option<String>, SnakeError> {
+        // Drop the keys still queued from the game, so they don't end up in the name.
+        while self.context.event_pump.poll_event().is_some() {}
+
+        let text_input = self.context.canvas.window().subsystem().text_input();
+        text_input.start();
+
+        let mut name = self.player_name.clone();
+        let title = format!("New high score: {} points!", score);
+        // Keys held down since the game would keep typing, so typing starts once a key is
+        // released, or after a moment.
+        let shown = Instant::now();
+        let mut released = false;
+
+        let result = loop {
+            let prompt = format!("Name: {}_", name);
+            self.draw_message(&[&title, &prompt, "Press Return to save it, or Escape to skip"])?;
+
+            match self.context.wait_event() {
+                Event::Quit {..} => {
+                    break None;
+                },
+                Event::KeyDown { repeat: true, ..} => {},
+                Event::KeyUp {..} => {
+                    released = true;
+                },
+                Event::KeyDown { keycode: Some(Keycode::Escape), ..} => {
+                    break None;
+                },
+                Event::KeyDown { keycode: Some(Keycode::Return | Keycode::KpEnter), ..}
+                    if !name.trim().is_empty() => {
+                    break Some(String::from(name.trim()));
+                },
+                Event::KeyDown { keycode: Some(Keycode::Backspace), ..} => {
+                    name.pop();
+                },
+                // There is no typing with a controller, but the name of the last player can be
+                // kept or skipped.
+                event @ Event::ControllerButtonDown {..} => {
+                    match self.key_action(&event, Context::Menu) {
+                        Some(Action::Confirm) if !name.trim().is_empty() => break Some(String::from(name.trim())),
+                        Some(Action::Back) => break None,
+                        _ => {}
+                    }
+                },
+                Event::TextInput { text, .. } if released || shown.elapsed() >= INPUT_DELAY => {
+                    for c in text.chars().filter(|c| !c.is_control()) {
+                        if name.chars().count() < scores::MAX_NAME_LENGTH {
+                            name.push(c);
+                        }
+                    }
+                },
+                _ => {}
+            }
+        };
+
+        text_input.stop();
+        Ok(result)
+    }
+
+    /// Records the game that just ended in the high scores, if it made it there, asking the
+    /// player for a name. Returns the position it got in the table, if any. Replays, campaigns
+    /// and games played by a bot don't count.
+    fn record_high_score(&mut self) -> Result<Option<usize>, SnakeError> {
+        if self.playback.is_some() || self.campaign.is_some() || self.bot.is_some() {
+            return Ok(None);
+        }
+
+        let board = Board::of(&self.sim);
+        let mut scores = self.load_high_scores();
+        if scores.rank(&board, self.sim.score()).is_none() {
+            return Ok(None);
+        }
+
+        let name = match self.enter_name(self.sim.score())? {
+            Some(name) => name,
+            None => return Ok(None),
+        };
+
+        let rank = scores.insert(&board, HighScore::new(&name, &self.sim, self.play_time));
+        self.player_name = name;
+
+        if let Some(dir) = paths::data_dir() {
+            if let Err(e) = scores.save(&dir.join(HIGH_SCORES)) {
+                eprintln!("Could not save the high scores: {}", e);
+            }
+        }
+
+        Ok(rank)
+    }
+
+    /// Loads the campaign saved in the data directory, if there is one.
+    fn load_campaign(&self) -> Option<Campaign> {
+        let path = paths::data_dir()?.join(CAMPAIGN_PROGRESS);
+        if !path.exists() {
+            return None;
+        }
+
+        match Campaign::load(&path, Level::bundled()) {
+            Ok(campaign) => Some(campaign),
+            Err(e) => {
+                eprintln!("Could not resume the campaign: {}", e);
+                None
+            },
+        }
+    }
+
+    /// Saves the progress of the campaign in the data directory or, once the campaign is over,
+    /// removes it from there.
+    fn save_campaign(&self, status: CampaignStatus) {
+        let (campaign, path) = match (&self.campaign, paths::data_dir()) {
+            (Some(campaign), Some(dir)) => (campaign, dir.join(CAMPAIGN_PROGRESS)),
+            _ => return,
+        };
+
+        let result = match status {
+            CampaignStatus::PLAYING => campaign.save(&path),
+            _ => fs::remove_file(&path).or_else(|e| match e.kind() {
+                io::ErrorKind::NotFound => Ok(()),
+                _ => Err(e),
+            }),
+        };
+
+        if let Err(e) = result {
+            eprintln!("Could not save the campaign: {}", e);
+        }
+    }
+
+    /// Shows the level of the campaign about to be played, along with the lives and score so
+    /// far. Any key starts the level; going back returns to the menu, where the campaign can be
+    /// resumed later.
+    fn level_intro_loop(&mut self) -> Result<GameTransition, SnakeError> {
+        let campaign = match &self.campaign {
+            Some(campaign) => campaign,
+            None => return Ok(GameTransition::MENU),
+        };
+
+        let title  = format!("Level {} of {}: {}", campaign.stage() + 1, campaign.stages(), campaign.level().name);
+        let target = format!("Score {} points to clear it", campaign.level().target);
+        let status = format!("Lives: {}   Score: {}", campaign.lives(), campaign.score());
+        self.draw_message(&[&title, &target, &status, "Press any key to start..."])?;
+
+        loop {
+            let event = self.context.wait_event();
+            if self.key_action(&event, Context::Menu) == Some(Action::Back) {
+                return Ok(GameTransition::MENU);
+            }
+
+            match event {
+                Event::Quit {..} => {
+                    return Ok(GameTransition::EXIT);
+                },
+                Event::KeyDown {..} | Event::ControllerButtonDown {..} => {
+                    return Ok(GameTransition::PLAY);
+                },
+                _ => {}
+            }
+        }
+    }
+
+    /// The state that follows a game that just ended. Outside of a campaign, that is simply
+    /// `state`. In a campaign, the result of the level decides: the next level (or the same one
+    /// again, with a life less) is introduced until the campaign is either completed or lost.
+    fn after_game(&mut self, state: GameState) -> GameState {
+        let status = match &mut self.campaign {
+            Some(campaign) => campaign.finish_level(self.sim.score()),
+            None => return state,
+        };
+
+        self.save_campaign(status);
+
+        match status {
+            CampaignStatus::PLAYING => {
+                // The end-of-game screens are skipped, so the replay is saved here.
+                self.save_replay();
+                GameState::INTRO
+            },
+            CampaignStatus::COMPLETED => GameState::WON,
+            CampaignStatus::LOST => GameState::GAMEOVER,
+        }
+    }
+
+
+    /// The menu shown over the `GameArea` while the game is paused. A level of a campaign cannot
+    /// be restarted, or it would not cost a life.
+    fn build_pause_menu(&self) -> Menu {
+        let grid = vec![String::from("Off"), String::from("On")];
+
+        let mut menu = Menu::new("Paused");
+        menu.action("Resume", MenuId::RESUME);
+        if self.campaign.is_none() {
+            menu.action("Restart", MenuId::RESTART);
+        }
+        menu.submenu("Settings")
+                .selector("Grid", MenuId::GRID, grid, self.show_grid as usize)
+            .end_submenu()
+            .action("Quit to menu", MenuId::QUIT);
+
+        menu
+    }
+
+    /// Draws the game as it was left, dimmed, with `menu` over it.
+    fn draw_paused(&mut self, menu: &Menu) -> Result<(), SnakeError> {
+        self.render_frame(self.show_grid)?;
+
+        self.context.canvas.set_blend_mode(BlendMode::Blend);
+        self.context.canvas.set_draw_color(Color::RGBA(0, 0, 0, 160));
+        self.context.canvas.fill_rect(None).map_err(SnakeError::Render)?;
+        self.context.canvas.set_blend_mode(BlendMode::None);
+
+        let texture_creator = self.context.canvas.texture_creator();
+        self.render_menu(&texture_creator, menu, self.display.height as i32/2, true)?;
+        self.context.canvas.present();
+
+        Ok(())
+    }
+
+    /// Handles the paused loop. From here we can return to `PLAYING`, start the game over, go
+    /// back to the menu or give up with `LOSE`.
+    ///
+    fn paused_loop(&mut self) -> Result<GameTransition, SnakeError> {
+        // A boost still held when pausing would never see its key released.
+        self.set_speed(self.normal_speed);
+
+        let mut menu = self.build_pause_menu();
+        self.draw_paused(&menu)?;
+
+        loop {
+            let event = self.context.wait_event();
+
+            match self.key_action(&event, Context::Menu) {
+                Some(Action::Pause) => return Ok(GameTransition::PLAY),
+                Some(Action::Quit) => return Ok(GameTransition::LOSE),
+                _ => {}
+            }
+
+            if let Event::Quit {..} = event {
+                return Ok(GameTransition::EXIT);
+            }
+
+            let menu_event = match self.menu_input(&mut menu, event) {
+                Ok(menu_event) => menu_event,
+                Err(Event::Window { win_event: WindowEvent::Exposed, .. }) => MenuEvent::NONE,
+                Err(_) => continue,
+            };
+
+            match menu_event {
+                MenuEvent::SELECTED(MenuId::RESUME) | MenuEvent::CLOSED => {
+                    return Ok(GameTransition::PLAY);
+                },
+                MenuEvent::SELECTED(MenuId::RESTART) => {
+                    return Ok(GameTransition::RESTART);
+                },
+                MenuEvent::SELECTED(MenuId::QUIT) => {
+                    return Ok(GameTransition::MENU);
+                },
+                MenuEvent::CHANGED(id, value) => {
+                    self.apply_setting(id, value);
+                },
+                _ => {}
+            }
+
+            self.draw_paused(&menu)?;
+        }
+    }
+
+
+    /// This is the loop for the `PLAYING` state. From this state we should be able to transition
+    /// to either:
+    ///     - PAUSED: If the user presses the space bar key, or the window loses the focus.
+    ///     - GAMEOVER: If the `Snake` collides with itself or with the walls.
+    ///     - WON: If the `Snake` fills the whole `GameArea` or, in a campaign, reaches the
+    ///       target score of the level.
+    ///
+    /// Otherwise, the game continues _ad infinitum`.
+    /// 
+    fn game_loop(&mut self) -> Result<GameTransition, SnakeError> {
+        if self.versus.is_some() {
+            return self.versus_loop();
+        }
+
+        loop {
+            let event = self.context.wait_event();
+
+            if let Some(action) = self.key_action(&event, Context::Game) {
+                let direction = self.sim.snake().direction;
+
+                match action {
+                    Action::Quit | Action::Back => {
+                        return Ok(GameTransition::LOSE);
+                    },
+                    Action::Pause => {
+                        return Ok(GameTransition::PAUSE);
+                    },
+                    Action::TurnLeft  => self.turns.push(Direction::LEFT, direction),
+                    Action::TurnRight => self.turns.push(Direction::RIGHT, direction),
+                    Action::TurnUp    => self.turns.push(Direction::UP, direction),
+                    Action::TurnDown  => self.turns.push(Direction::DOWN, direction),
+                    Action::Boost => {
+                        self.set_speed(self.normal_speed / BOOST_FACTOR);
+                    },
+                    Action::ToggleGrid => {
+                        self.show_grid = !self.show_grid;
+                    },
+                    Action::Confirm => {},
+                }
+
+                continue;
+            }
+
+            match event
+            {
+                Event::User {..} => {
+                    // The only user event we have is the timer, this means
+                    // here we need to advance the game and draw the current frame.
+                    let input = match &mut self.playback {
+                        Some(playback) => {
+                            if playback.finished(&self.sim) {
+                                return Ok(GameTransition::LOSE);
+                            }
+
+                            playback.input(&self.sim)
+                        },
+                        None => match self.drivers.first_mut() {
+                            Some(bot) => bot.decide(&self.sim.view()),
+                            None => self.turns.decide(&self.sim.view()),
+                        },
+                    };
+
+                    let tick = self.sim.ticks();
+                    let direction = self.sim.snake().direction;
+                    let outcome = self.sim.step(input);
+
+                    if self.sim.snake().direction != direction {
+                        self.replay.record(tick, self.sim.snake().direction);
+                    }
+
+                    match outcome {
+                        StepOutcome::COLLIDED => {
+                            return Ok(GameTransition::LOSE);
+                        },
+                        StepOutcome::WON => {
+                            return Ok(GameTransition::WIN);
+                        },
+                        StepOutcome::ATE if self.campaign.as_ref()
+                            .is_some_and(|c| c.target_reached(self.sim.score())) => {
+                            return Ok(GameTransition::WIN);
+                        },
+                        _ => {}
+                    }
+
+                    self.draw_frame(self.show_grid)?;
+                },
+
+                Event::Quit {..} => {
+                    return Ok(GameTransition::LOSE);
+                },
+
+                Event::Window { win_event: WindowEvent::FocusLost, .. } => {
+                    return Ok(GameTransition::PAUSE);
+                },
+
+                event if Input::released(&event).is_some_and(|i| self.keymap.inputs(Action::Boost).contains(&i)) => {
+                    self.set_speed(self.normal_speed);
+                },
+
+                _ => {}
+            }
+        } // loop
+    }
+
+
+    /// The player of a versus game steered by the key or button pressed in `event`, if any, with
+    /// the direction to steer to.
+    fn steer(&self, event: &Event) -> Option<(usize, Direction)> {
+        let input = Input::pressed(event)?;
+        let controller = match event {
+            Event::ControllerButtonDown { which, .. } => {
+                self.context.controllers.iter().position(|(c, _)| c.instance_id() == *which)
+            },
+            _ => None,
+        };
+
+        (0..self.players).find_map(|player| {
+            Steering::of_player(player).turn(input, controller).map(|direction| (player, direction))
+        })
+    }
+
+    /// The loop for the `PLAYING` state of a versus game. Every player steers their own `Snake`,
+    /// while the other actions, such as pausing, are shared; the keys bound to the turns also
+    /// steer the first one. On every tick, each `Snake` goes where its `Controller` decides.
+    /// There is no boost, since it would speed everyone up. Once the round is over, it is
+    /// recorded in the scoreboard and the transition is `LOSE`, whoever won.
+    fn versus_loop(&mut self) -> Result<GameTransition, SnakeError> {
+        loop {
+            let event = self.context.wait_event();
+
+            if let Some((player, direction)) = self.steer(&event) {
+                let current = self.versus.as_ref().unwrap().players()[player].snake().direction;
+                self.drivers[player].input(direction, current);
+                continue;
+            }
+
+            let turn = match self.key_action(&event, Context::Game) {
+                Some(Action::TurnLeft)  => Some(Direction::LEFT),
+                Some(Action::TurnRight) => Some(Direction::RIGHT),
+                Some(Action::TurnUp)    => Some(Direction::UP),
+                Some(Action::TurnDown)  => Some(Direction::DOWN),
+                _ => None,
+            };
+
+            if let Some(direction) = turn {
+                let current = self.versus.as_ref().unwrap().players()[0].snake().direction;
+                self.drivers[0].input(direction, current);
+                continue;
+            }
+
+            match self.key_action(&event, Context::Game) {
+                Some(Action::Quit | Action::Back) => {
+                    return Ok(GameTransition::LOSE);
+                },
+                Some(Action::Pause) => {
+                    return Ok(GameTransition::PAUSE);
+                },
+                Some(Action::ToggleGrid) => {
+                    self.show_grid = !self.show_grid;
+                    continue;
+                },
+                Some(_) => continue,
+                None => {},
+            }
+
+            match event {
+                Event::User {..} => {
+                    let versus = self.versus.as_mut().unwrap();
+                    let inputs: Vec<Option<Direction>> = self.drivers.iter_mut().enumerate()
+                        .map(|(i, driver)| match versus.players()[i].alive() {
+                            true  => driver.decide(&versus.view(i)),
+                            false => None,
+                        })
+                        .collect();
+
+                    if versus.step(&inputs) != RoundOutcome::PLAYING {
+                        self.scoreboard.record(versus);
+                        return Ok(GameTransition::LOSE);
+                    }
+
+                    self.draw_frame(self.show_grid)?;
+                },
+
+                Event::Quit {..} => {
+                    return Ok(GameTransition::LOSE);
+                },
+
+                Event::Window { win_event: WindowEvent::FocusLost, .. } => {
+                    return Ok(GameTransition::PAUSE);
+                },
+
+                _ => {}
+            }
+        }
+    }
+
+
+    /// Draws the border of the `GameArea`. Walls are a solid red line; when the `Snake` can go
+    /// through the edges, the border is a dashed green line instead, with a gap every other cell.
+    fn draw_border(&mut self) -> Result<(), SnakeError> {
+        let area = self.display.game_area;
+
+        let mode = self.versus.as_ref().map_or(self.sim.mode(), Versus::mode);
+        if mode == BoardMode::WALLS {
+            self.context.canvas.set_draw_color(Color::RGB(255, 0, 0));
+            return self.context.canvas.draw_rect(area).map_err(SnakeError::Render);
+        }
+
+        self.context.canvas.set_draw_color(Color::RGB(0, 160, 0));
+
+        let cell = self.display.cell_size as i32;
+        let (left, top)     = (area.left(), area.top());
+        let (right, bottom) = (area.right() - 1, area.bottom() - 1);
+
+        for x in (0..self.display.hcells as i32).step_by(2) {
+            let (from, to) = (left + x * cell, left + (x + 1) * cell - 1);
+            self.context.canvas.draw_line((from, top), (to, top)).map_err(SnakeError::Render)?;
+            self.context.canvas.draw_line((from, bottom), (to, bottom)).map_err(SnakeError::Render)?;
+        }
+
+        for y in (0..self.display.vcells as i32).step_by(2) {
+            let (from, to) = (top + y * cell, top + (y + 1) * cell - 1);
+            self.context.canvas.draw_line((left, from), (left, to)).map_err(SnakeError::Render)?;
+            self.context.canvas.draw_line((right, from), (right, to)).map_err(SnakeError::Render)?;
+        }
+
+        Ok(())
+    }
+
+
+    /// Draws the current frame, as held by the `Simulation`.
+    ///
+    fn draw_frame(&mut self, draw_grid:bool) -> Result<(), SnakeError> {
+        self.render_frame(draw_grid)?;
+        self.context.canvas.present();
+
+        Ok(())
+    }
+
+    /// Renders the current frame, as held by the `Simulation`, without presenting it yet.
+    ///
+    fn render_frame(&mut self, draw_grid:bool) -> Result<(), SnakeError> {
+        let texture_creator = self.context.canvas.texture_creator();
+        let score_surface : sdl2::surface::Surface;
+        let texture : sdl2::render::Texture;
+
+        self.context.canvas.set_draw_color(Color::RGB(255, 255, 255));
+        self.context.canvas.clear();
+
+        self.draw_border()?;
+
+        if draw_grid {
+            self.context.canvas.set_draw_color(Color::RGB(100, 100, 100));
+            for r in &self.display.grid {
+                self.context.canvas.draw_rect(*r).map_err(SnakeError::Render)?;
+            }
+        }
+
+        let (walls, food) = match &self.versus {
+            Some(versus) => (versus.walls(), versus.food()),
+            None => (self.sim.walls(), self.sim.food()),
+        };
+
+        self.context.canvas.set_draw_color(Color::RGB(80, 80, 80));
+        for w in walls {
+            self.context.canvas.fill_rect(create_rect(&self.display, w)).map_err(SnakeError::Render)?;
+        }
+
+        match &self.versus {
+            Some(versus) => {
+                for (player, (_, head, body)) in versus.players().iter().zip(PLAYER_COLORS) {
+                    let (head, body) = match player.alive() {
+                        true  => (head, body),
+                        false => (DEAD_COLOR, DEAD_COLOR),
+                    };
+                    render_snake(&mut self.context.canvas, &self.display, player.snake(), head, body)?;
+                }
+            },
+            None => {
+                let (_, head, body) = PLAYER_COLORS[0];
+                render_snake(&mut self.context.canvas, &self.display, self.sim.snake(), head, body)?;
+            },
+        }
+
+        if let Some(food) = food {
+            self.context.canvas.set_draw_color(Color::RGB(0,0,0));
+            self.context.canvas.fill_rect(create_rect(&self.display, &food)).map_err(SnakeError::Render)?;
+        }
+
+        let score_message = match &self.versus {
+            Some(versus) => {
+                let scores: Vec<String> = versus.players().iter().zip(PLAYER_COLORS)
+                    .map(|(player, (name, _, _))| format!("{}: {}", name, player.score()))
+                    .collect();
+                format!("Round {}   {}", self.scoreboard.rounds() + 1, scores.join("   "))
+            },
+            None => format!("Score: {}", self.sim.score()),
+        };
+        score_surface  = self.font
+            .render(&score_message)
+            .solid(Color::RGB(0, 0, 0))
+            .map_err(|e| SnakeError::Render(e.to_string()))?;
+        texture = texture_creator
+            .create_texture_from_surface(&score_surface)
+            .map_err(|e| SnakeError::Texture(e.to_string()))?;
+
+        // The scores of a versus game take more room; they are drawn at the size of the text.
+        let mut score_rect = self.score_rect;
+        if self.versus.is_some() {
+            let width = score_surface.width() * score_rect.height() / score_surface.height().max(1);
+            score_rect.set_width(width.min(self.display.game_area.width()));
+        }
+
+        self.context.canvas.copy(&texture, None, Some(score_rect))
+            .map_err(SnakeError::Render)?;
+
+        Ok(())
+    }
+
+    /// Clears the screen and shows `lines` of text in the middle of the `GameArea`, one below
+    /// the other.
+    fn draw_message(&mut self, lines: &[&str]) -> Result<(), SnakeError> {
+        self.context.canvas.set_draw_color(Color::RGB(255, 255, 255));
+        self.context.canvas.clear();
+
+        self.context.canvas.set_draw_color(Color::RGB(255, 0, 0));
+        self.context.canvas.draw_rect(self.display.game_area).map_err(SnakeError::Render)?;
+
+        let top = self.display.height as i32/2 - (lines.len() as i32 * 2 * SPACING as i32)/2;
+        self.render_lines(lines, top)?;
+
+        self.context.canvas.present();
+
+        Ok(())
+    }
+
+    /// Renders `lines` of text centered horizontally, one below the other, from `top` down.
+    fn render_lines(&mut self, lines: &[&str], top: i32) -> Result<(), SnakeError> {
+        let texture_creator = self.context.canvas.texture_creator();
+
+        for (i, line) in lines.iter().enumerate() {
+            let (fw, fh) = self.font.size_of(line).map_err(|e| SnakeError::Render(e.to_string()))?;
+
+            let line_surface  = self.font
+                .render(line)
+                .solid(Color::RGB(0, 0, 0))
+                .map_err(|e| SnakeError::Render(e.to_string()))?;
+            let line_texture = texture_creator
+                .create_texture_from_surface(&line_surface)
+                .map_err(|e| SnakeError::Texture(e.to_string()))?;
+            let line_rect = Rect::new(self.display.width as i32/2 - fw as i32/2,
+                                      top + (2 * i as i32 + 1) * SPACING as i32 - fh as i32/2,
+                                      fw, fh);
+            self.context.canvas.copy(&line_texture, None, Some(line_rect))
+                .map_err(SnakeError::Render)?;
+        }
+
+        Ok(())
+    }
+
+    /// Waits for any key on one of the screens that only show information, e.g. the high scores.
+    /// Closing the window exits the game; any key goes back to where the screen was opened from.
+    fn wait_any_key(&mut self) -> Result<GameTransition, SnakeError> {
+        loop {
+            let event = self.context.wait_event();
+            match event {
+                Event::Quit {..} => {
+                    return Ok(GameTransition::EXIT);
+                },
+                Event::KeyDown {..} | Event::ControllerButtonDown {..} => {
+                    return Ok(GameTransition::PLAY);
+                },
+                _ => {}
+            }
+        }
+    }
+
+    /// Saves the recording of the game that just ended in the data directory. A replay that is
+    /// being played back is not saved again.
+    fn save_replay(&mut self) {
+        if self.playback.is_some() {
+            return;
+        }
+
+        self.replay.finish(self.sim.ticks(), self.sim.score());
+
+        if let Some(dir) = paths::data_dir() {
+            if let Err(e) = self.replay.save(&dir.join(LAST_REPLAY)) {
+                eprintln!("Could not save the replay: {}", e);
+            }
+        }
+    }
+
+    /// The statistics of the game that just ended, one per line, depending on whether it was
+    /// `won`. `rank` is the position the game got in the high scores, if any.
+    fn game_stats(&self, won: bool, rank: Option<usize>) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        if let Some(campaign) = &self.campaign {
+            lines.push(format!("Campaign score: {}", campaign.score()));
+        }
+
+        lines.push(format!("Score: {}   Length: {}", self.sim.score(), self.sim.snake().body.len()));
+        lines.push(format!("Time: {}   Food eaten: {}", scores::format_duration(self.play_time), self.sim.food_eaten()));
+
+        let cause = match self.sim.collision() {
+            Some(Collision::EDGE) => "Ran into the edge of the board",
+            Some(Collision::WALL) => "Ran into a wall",
+            Some(Collision::BODY) => "Bit its own tail",
+            Some(Collision::SNAKE | Collision::HEAD) => "Ran into another snake",
+            None if !won => "Gave up",
+            None if self.sim.food().is_none() => "Filled the whole board",
+            None => "Cleared every level",
+        };
+        lines.push(String::from(cause));
+
+        if let Some(rank) = rank {
+            lines.push(format!("New high score: #{}!", rank + 1));
+        }
+
+        lines.push(format!("Seed: {}", self.sim.seed()));
+        lines
+    }
+
+    /// The scoreboard of a versus match, once a round is over: the rounds won by every player,
+    /// and how they did in this round.
+    fn versus_stats(&self, versus: &Versus) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        for (i, (player, (name, _, _))) in versus.players().iter().zip(PLAYER_COLORS).enumerate() {
+            let fate = match player.collision() {
+                Some(Collision::EDGE)  => "Hit the edge",
+                Some(Collision::WALL)  => "Hit a wall",
+                Some(Collision::BODY)  => "Bit itself",
+                Some(Collision::SNAKE) => "Hit another snake",
+                Some(Collision::HEAD)  => "Head-on crash",
+                None if versus.outcome() == RoundOutcome::PLAYING => "Gave up",
+                None => "Survived",
+            };
+
+            let driver = match i >= self.players || (i == 0 && self.bot.is_some()) {
+                true  => String::from(self.drivers[i].name()),
+                false => Steering::of_player(i).label(),
+            };
+
+            let wins = self.scoreboard.wins(i);
+            lines.push(format!("{} ({}): {} {}   Score: {}   {}", name, driver,
+                               wins, if wins == 1 { "win" } else { "wins" }, player.score(), fate));
+        }
+
+        if self.scoreboard.draws() > 0 {
+            lines.push(format!("Draws: {}", self.scoreboard.draws()));
+        }
+
+        if self.scoreboard.winner().is_none() {
+            lines.push(format!("First to win {} rounds takes the match", ROUNDS_TO_WIN));
+        }
+
+        lines.push(format!("Time: {}   Seed: {}", scores::format_duration(self.play_time), versus.seed()));
+        lines
+    }
+
+    /// The choices offered once a game is over. A replay can only be watched again; a versus
+    /// match goes on with the next round until someone wins it.
+    fn build_end_menu(&self, title: &str) -> Menu {
+        let mut menu = Menu::new(title);
+
+        if self.playback.is_some() {
+            menu.action("Watch again", MenuId::RESTART);
+        }
+        else if self.versus.is_some() {
+            let next = match self.scoreboard.winner() {
+                Some(_) => "Rematch",
+                None => "Next round",
+            };
+
+            menu.action(next, MenuId::RESTART)
+                .action("Menu", MenuId::QUIT);
+        }
+        else {
+            menu.action("Retry", MenuId::RESTART)
+                .action("Menu", MenuId::QUIT);
+        }
+
+        menu.action("Quit", MenuId::EXIT);
+        menu
+    }
+
+    /// Draws the statistics of the game that just ended, `stats`, with `menu` below them.
+    fn draw_end_of_game(&mut self, stats: &[String], menu: &Menu) -> Result<(), SnakeError> {
+        self.context.canvas.set_draw_color(Color::RGB(255, 255, 255));
+        self.context.canvas.clear();
+
+        self.context.canvas.set_draw_color(Color::RGB(255, 0, 0));
+        self.context.canvas.draw_rect(self.display.game_area).map_err(SnakeError::Render)?;
+
+        // Every line of text, and every entry of the menu, is `2 * SPACING` high. The title of the
+        // menu takes two.
+        let stats_height = stats.len() as i32 * 2 * SPACING as i32;
+        let menu_height = (menu.labels().len() as i32 + 2) * 2 * SPACING as i32;
+        let top = self.display.height as i32/2 - (stats_height + menu_height)/2;
+
+        let lines: Vec<&str> = stats.iter().map(String::as_str).collect();
+        self.render_lines(&lines, top)?;
+
+        let texture_creator = self.context.canvas.texture_creator();
+        let menu_center = top + stats_height + menu_height/2;
+        self.render_menu(&texture_creator, menu, menu_center, false)?;
+
+        self.context.canvas.present();
+
+        Ok(())
+    }
+
+    /// Shows the end of a game that was `won` or not, under `title`: its statistics and what to
+    /// do next. Once the game was saved and recorded in the high scores, the player can retry, go
+    /// back to the menu or quit.
+    fn end_of_game_loop(&mut self, title: &str, won: bool) -> Result<GameTransition, SnakeError> {
+        // Versus games are neither recorded nor ranked: they have no single score.
+        let stats = match &self.versus {
+            Some(versus) => self.versus_stats(versus),
+            None => {
+                self.save_replay();
+                let rank = self.record_high_score()?;
+                self.game_stats(won, rank)
+            },
+        };
+        let mut menu = self.build_end_menu(title);
+        self.draw_end_of_game(&stats, &menu)?;
+
+        let shown = Instant::now();
+
+        loop {
+            let event = self.context.wait_event();
+
+            let event = match event {
+                Event::Quit {..} => {
+                    return Ok(GameTransition::EXIT);
+                },
+                Event::KeyDown { repeat: true, .. } => continue,
+                Event::KeyDown {..} | Event::MouseButtonDown {..} |
+                Event::ControllerButtonDown {..} if shown.elapsed() < INPUT_DELAY => continue,
+                event => event,
+            };
+
+            let menu_event = match self.menu_input(&mut menu, event) {
+                Ok(menu_event) => menu_event,
+                Err(Event::Window { win_event: WindowEvent::Exposed, .. }) => MenuEvent::NONE,
+                Err(_) => continue,
+            };
+
+            match menu_event {
+                MenuEvent::SELECTED(MenuId::RESTART) => {
+                    return Ok(GameTransition::RESTART);
+                },
+                MenuEvent::SELECTED(MenuId::QUIT) => {
+                    return Ok(GameTransition::MENU);
+                },
+                MenuEvent::SELECTED(MenuId::EXIT) | MenuEvent::CLOSED => {
+                    return Ok(GameTransition::EXIT);
+                },
+                _ => {}
+            }
+
+            self.draw_end_of_game(&stats, &menu)?;
+        }
+    }
+
+    /// This loop represents the `GAMEOVER` window that is shown when `GameState::PLAYING +
+    /// GameTransition::LOSE` occurrs. 
+    fn game_over_loop(&mut self) -> Result<GameTransition, SnakeError> {
+        if let Some(versus) = &self.versus {
+            let title = match (versus.outcome(), self.scoreboard.winner()) {
+                (RoundOutcome::WON(_), Some(winner)) => format!("{} wins the match!", PLAYER_COLORS[winner].0),
+                (RoundOutcome::WON(winner), None) => format!("{} wins the round!", PLAYER_COLORS[winner].0),
+                (RoundOutcome::DRAW, _) => String::from("Draw!"),
+                (RoundOutcome::PLAYING, _) => String::from("Round given up"),
+            };
+
+            return self.end_of_game_loop(&title, false);
+        }
+
+        let title = match self.campaign {
+            Some(_) => "No lives left!",
+            None => "You lost!",
+        };
+
+        self.end_of_game_loop(title, false)
+    }
+
+    /// This loop represents the `WON` window that is shown when `GameState::PLAYING +
+    /// GameTransition::WIN` occurrs, i.e. the `Snake` filled the whole `GameArea` or the last
+    /// level of the campaign was cleared.
+    fn game_won_loop(&mut self) -> Result<GameTransition, SnakeError> {
+        let title = match self.campaign {
+            Some(_) => "You completed the campaign!",
+            None => "You won!",
+        };
+
+        self.end_of_game_loop(title, true)
+    }
+
+    /// Starts the game over. A replay is played back from the beginning, and a campaign from its
+    /// first level; otherwise, a new game starts on the same level. Returns the state to go to.
+    fn restart(&mut self) -> Result<GameState, SnakeError> {
+        if let Some(playback) = &self.playback {
+            let replay = playback.replay().clone();
+            self.sim = replay.simulation().map_err(SnakeError::Config)?;
+            self.playback = Some(Playback::new(replay));
+            self.play_time = Duration::ZERO;
+            self.set_speed(self.normal_speed);
+            return Ok(GameState::PLAYING);
+        }
+
+        if self.campaign.is_some() {
+            self.campaign = Some(Campaign::new(Level::bundled()));
+            return Ok(GameState::INTRO);
+        }
+
+        if self.scoreboard.winner().is_some() {
+            self.scoreboard = Match::new(self.snakes());
+        }
+
+        self.reset()?;
+        Ok(GameState::PLAYING)
+    }
+
+    /// The `Game` is controlled by a `FSM`: every `GameState` has its own screen, whose loop
+    /// returns a `GameTransition` telling which state comes next, until the player exits.
+    fn start(&mut self) -> Result<(), SnakeError> {
+        loop {
+            let transition;// = GameTransition::EXIT;
+            let mut handled = true; // Whether the transition was already processed by an inner state handle.
+            // Unless explicitly set to false in a state handle, we assume the transition was already processed.
+
+            match self.context.current_state {
+
+                GameState::STARTING => {
+                    transition = self.game_starting()?;
+                    match transition
+                    {
+                        GameTransition::PLAY if self.campaign.is_some() => {
+                            self.context.current_state = GameState::INTRO;
+                        },
+                        GameTransition::PLAY => {
+                            self.reset()?;
+                            self.context.current_state = GameState::PLAYING;
+                        },
+                        _ => { handled = false; }
+                    }
+                },
+
+                GameState::INTRO => {
+                    transition = self.level_intro_loop()?;
+                    match transition
+                    {
+                        GameTransition::PLAY => {
+                            self.reset()?;
+                            self.context.current_state = GameState::PLAYING;
+                        },
+                        GameTransition::MENU => {
+                            self.context.current_state = GameState::STARTING;
+                        },
+                        _ => { handled = false; }
+                    }
+                },
+
+                GameState::PLAYING => {
+                    let resumed = Instant::now();
+                    transition = self.game_loop()?;
+                    self.play_time += resumed.elapsed();
+                    match transition
+                    {
+                        GameTransition::PAUSE => {
+                            self.context.current_state = GameState::PAUSED;
+                        },
+                        GameTransition::LOSE => {
+                            self.context.current_state = self.after_game(GameState::GAMEOVER);
+                        },
+                        GameTransition::WIN => {
+                            self.context.current_state = self.after_game(GameState::WON);
+                        },
+                        _ => { handled = false; }
+                    }
+                },
+
+                GameState::PAUSED => {
+                    transition = self.paused_loop()?;
+                    match transition
+                    {
+                        GameTransition::PLAY => {
+                            self.context.current_state = GameState::PLAYING;
+                        },
+                        GameTransition::RESTART => {
+                            self.context.current_state = self.restart()?;
+                        },
+                        GameTransition::LOSE => {
+                            self.context.current_state = self.after_game(GameState::GAMEOVER);
+                        },
+                        GameTransition::MENU => {
+                            self.context.current_state = GameState::STARTING;
+                        },
+                        _ => { handled = false; }
+                    }
+                },
+
+                GameState::GAMEOVER => {
+                    transition = self.game_over_loop()?;
+                    match transition
+                    {
+                        GameTransition::RESTART => {
+                            self.context.current_state = self.restart()?;
+                        },
+                        GameTransition::MENU => {
+                            self.context.current_state = GameState::STARTING;
+                        },
+                        _ => { handled = false; }
+                    }
+                },
+
+                GameState::WON => {
+                    transition = self.game_won_loop()?;
+                    match transition
+                    {
+                        GameTransition::RESTART => {
+                            self.context.current_state = self.restart()?;
+                        },
+                        GameTransition::MENU => {
+                            self.context.current_state = GameState::STARTING;
+                        },
+                        _ => { handled = false; }
+                    }
+                }
+
+            }
+
+            if handled {
+                continue;
+            }
+            else if transition == GameTransition::EXIT {
+                // Global transition: finish game.
+                return Ok(());
+            }
+            else {
+                return Err(SnakeError::InvalidTransition {
+                    state      : format!("{:?}", self.context.current_state),
+                    transition : format!("{:?}", transition),
+                });
+            }
+        }
+    }
+}
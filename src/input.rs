@@ -0,0 +1,78 @@
+//! Handling of the player's input between two ticks of the game.
+
+use std::collections::VecDeque;
+
+use crate::ai::Controller;
+use crate::sim::{Direction, View};
+
+
+/// How many turns can be waiting at once. Anything typed beyond that is dropped.
+const MAX_QUEUED_TURNS: usize = 3;
+
+
+/// The `Snake` only moves once per tick, but a fast player can request several turns within a
+/// single tick. Rather than keeping the last one only, turns are queued and applied one per
+/// tick, so quick sequences such as "up, left" are not lost and cannot make the `Snake` reverse
+/// into itself.
+pub struct TurnQueue {
+    turns       : VecDeque<Direction>,
+}
+
+
+impl TurnQueue {
+
+    pub fn new() -> TurnQueue {
+        TurnQueue {
+            turns: VecDeque::with_capacity(MAX_QUEUED_TURNS),
+        }
+    }
+
+    /// Queues a turn to `direction`. The turn is checked against the direction the `Snake` will
+    /// have on the tick before it is applied: the last queued turn, or `current` if there is
+    /// none. Turns that would not change the direction or would reverse it are ignored.
+    pub fn push(&mut self, direction: Direction, current: Direction) {
+        let previous = self.turns.back().copied().unwrap_or(current);
+
+        if direction == previous || direction == previous.opposite() {
+            return;
+        }
+
+        if self.turns.len() < MAX_QUEUED_TURNS {
+            self.turns.push_back(direction);
+        }
+    }
+
+    /// The turn to apply on this tick, if any.
+    pub fn pop(&mut self) -> Option<Direction> {
+        self.turns.pop_front()
+    }
+
+    pub fn clear(&mut self) {
+        self.turns.clear();
+    }
+}
+
+
+impl Default for TurnQueue {
+    fn default() -> TurnQueue {
+        TurnQueue::new()
+    }
+}
+
+
+/// The human player drives their `Snake` through the same `Controller` interface as the
+/// computer: each tick, the next queued turn is taken.
+impl Controller for TurnQueue {
+
+    fn decide(&mut self, _view: &View) -> Option<Direction> {
+        self.pop()
+    }
+
+    fn input(&mut self, direction: Direction, current: Direction) {
+        self.push(direction, current);
+    }
+
+    fn name(&self) -> &str {
+        "Human"
+    }
+}
@@ -0,0 +1,440 @@
+//! The keys and controller buttons the player uses. Neither is handled directly: each one is
+//! mapped to an `Action`, and the screens of the game only deal with actions. The mapping can be
+//! changed in-game, and is saved in a plain text file where each line binds an action to a
+//! comma-separated list of SDL key names and controller buttons, prefixed with `pad:`:
+//!
+//! ```text
+//! turn-left = Left, H, pad:dpleft
+//! boost = Return, pad:a
+//! ```
+//!
+//! The left stick of a controller steers like its D-pad, see `Stick`. In versus games, every
+//! player steers with keys of their own instead, see `Steering`.
+//!
+//! Actions missing from the file keep their default keys. Empty lines and lines starting with
+//! `#` are ignored.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use sdl2::controller::{Axis, Button};
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+
+use crate::sim::Direction;
+
+
+/// How far the stick must be pushed, out of `i16::MAX`, before it counts.
+pub const STICK_DEAD_ZONE: i16 = 12000;
+
+/// Prefix of controller buttons in keymap files, so they are not mistaken for keys.
+const BUTTON_PREFIX: &str = "pad:";
+
+
+/// Something the player can do by pressing a key or a button.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Hash)]
+pub enum Action {
+    TurnLeft,
+    TurnRight,
+    TurnUp,
+    TurnDown,
+    /// Go faster while held.
+    Boost,
+    /// Pause the game, or resume it from the pause menu.
+    Pause,
+    ToggleGrid,
+    /// Give up the game being played.
+    Quit,
+    /// Select the highlighted entry of a menu.
+    Confirm,
+    /// Go back to the previous menu or screen.
+    Back,
+}
+
+
+/// Where an `Action` can be performed. An input may be bound to several actions, as long as they
+/// are never available at the same time.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Context {
+    /// While the `Snake` moves.
+    Game,
+    /// In menus.
+    Menu,
+}
+
+
+impl Action {
+
+    /// Every action, in the order the controls screen lists them.
+    pub const ALL: [Action; 10] = [
+        Action::TurnLeft, Action::TurnRight, Action::TurnUp, Action::TurnDown,
+        Action::Boost, Action::Pause, Action::ToggleGrid, Action::Quit,
+        Action::Confirm, Action::Back,
+    ];
+
+    /// Name of the action in keymap files.
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::TurnLeft   => "turn-left",
+            Action::TurnRight  => "turn-right",
+            Action::TurnUp     => "turn-up",
+            Action::TurnDown   => "turn-down",
+            Action::Boost      => "boost",
+            Action::Pause      => "pause",
+            Action::ToggleGrid => "toggle-grid",
+            Action::Quit       => "quit",
+            Action::Confirm    => "confirm",
+            Action::Back       => "back",
+        }
+    }
+
+    /// What the action does, as shown to the player.
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::TurnLeft   => "Turn left",
+            Action::TurnRight  => "Turn right",
+            Action::TurnUp     => "Turn up",
+            Action::TurnDown   => "Turn down",
+            Action::Boost      => "Go faster",
+            Action::Pause      => "Pause",
+            Action::ToggleGrid => "Show or hide the grid",
+            Action::Quit       => "Give up",
+            Action::Confirm    => "Confirm",
+            Action::Back       => "Back",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.into_iter().find(|a| a.name() == name)
+    }
+
+    /// Whether the action can be performed in `context`. Turning also moves through menus.
+    pub fn available_in(self, context: Context) -> bool {
+        match self {
+            Action::Boost | Action::ToggleGrid => context == Context::Game,
+            Action::Confirm => context == Context::Menu,
+            _ => true,
+        }
+    }
+
+    /// The inputs bound to the action unless the player changes them.
+    pub fn default_inputs(self) -> Vec<Input> {
+        use Input::{Key, Pad};
+
+        match self {
+            Action::TurnLeft   => vec![Key(Keycode::Left), Key(Keycode::H), Pad(Button::DPadLeft)],
+            Action::TurnRight  => vec![Key(Keycode::Right), Key(Keycode::L), Pad(Button::DPadRight)],
+            Action::TurnUp     => vec![Key(Keycode::Up), Key(Keycode::K), Pad(Button::DPadUp)],
+            Action::TurnDown   => vec![Key(Keycode::Down), Key(Keycode::J), Pad(Button::DPadDown)],
+            Action::Boost      => vec![Key(Keycode::Return), Pad(Button::A)],
+            Action::Pause      => vec![Key(Keycode::Space), Pad(Button::Start), Pad(Button::Y)],
+            Action::ToggleGrid => vec![Key(Keycode::G), Pad(Button::X)],
+            Action::Quit       => vec![Key(Keycode::Q), Pad(Button::Back)],
+            Action::Confirm    => vec![Key(Keycode::Return), Key(Keycode::KpEnter), Pad(Button::A)],
+            Action::Back       => vec![Key(Keycode::Escape), Key(Keycode::Backspace), Pad(Button::B)],
+        }
+    }
+}
+
+
+/// A key of the keyboard or a button of a game controller.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Hash)]
+pub enum Input {
+    Key(Keycode),
+    Pad(Button),
+}
+
+
+impl Input {
+
+    /// Name of the input in keymap files and on the controls screen.
+    pub fn name(self) -> String {
+        match self {
+            Input::Key(key) => key.name(),
+            Input::Pad(button) => format!("{}{}", BUTTON_PREFIX, button.string()),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Input> {
+        match name.strip_prefix(BUTTON_PREFIX) {
+            Some(button) => Button::from_string(button).map(Input::Pad),
+            None => Keycode::from_name(name).map(Input::Key),
+        }
+    }
+
+    /// Whether both inputs come from the same device, i.e. are both keys or both buttons.
+    pub fn same_device(self, other: Input) -> bool {
+        matches!((self, other), (Input::Key(_), Input::Key(_)) | (Input::Pad(_), Input::Pad(_)))
+    }
+
+    /// The key or controller button pressed in `event`, if any.
+    pub fn pressed(event: &Event) -> Option<Input> {
+        match event {
+            Event::KeyDown { keycode: Some(key), .. } => Some(Input::Key(*key)),
+            Event::ControllerButtonDown { button, .. } => Some(Input::Pad(*button)),
+            _ => None,
+        }
+    }
+
+    /// The key or controller button released in `event`, if any.
+    pub fn released(event: &Event) -> Option<Input> {
+        match event {
+            Event::KeyUp { keycode: Some(key), .. } => Some(Input::Key(*key)),
+            Event::ControllerButtonUp { button, .. } => Some(Input::Pad(*button)),
+            _ => None,
+        }
+    }
+}
+
+
+/// Two actions available in the same context bound to the same input.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Conflict {
+    pub input   : Input,
+    /// The action the input performs: the first one in `Action::ALL`.
+    pub first   : Action,
+    /// The action the input can no longer perform.
+    pub second  : Action,
+}
+
+
+/// The inputs bound to every `Action`.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Keymap {
+    /// Inputs of every action, in the order of `Action::ALL`.
+    inputs      : Vec<Vec<Input>>,
+}
+
+
+impl Keymap {
+
+    /// The default keymap.
+    pub fn new() -> Keymap {
+        Keymap {
+            inputs: Action::ALL.iter().map(|a| a.default_inputs()).collect(),
+        }
+    }
+
+    fn index(action: Action) -> usize {
+        Action::ALL.iter().position(|a| *a == action).unwrap()
+    }
+
+    /// The inputs bound to `action`.
+    pub fn inputs(&self, action: Action) -> &[Input] {
+        &self.inputs[Keymap::index(action)]
+    }
+
+    /// Binds `inputs` to `action`, replacing the inputs it had.
+    pub fn bind(&mut self, action: Action, inputs: Vec<Input>) {
+        self.inputs[Keymap::index(action)] = inputs;
+    }
+
+    /// Binds `input` to `action` instead of the inputs it had on the same device: picking a new
+    /// key keeps the buttons, and the other way around.
+    pub fn rebind(&mut self, action: Action, input: Input) {
+        let inputs = &mut self.inputs[Keymap::index(action)];
+        inputs.retain(|i| !i.same_device(input));
+        inputs.push(input);
+    }
+
+    /// The action `input` performs in `context`, if any.
+    pub fn action(&self, input: Input, context: Context) -> Option<Action> {
+        Action::ALL.into_iter()
+            .find(|a| a.available_in(context) && self.inputs(*a).contains(&input))
+    }
+
+    /// Inputs bound to more than one action available in the same context.
+    pub fn conflicts(&self) -> Vec<Conflict> {
+        let mut conflicts = Vec::new();
+
+        for (i, first) in Action::ALL.iter().enumerate() {
+            for second in &Action::ALL[i + 1..] {
+                let shared = [Context::Game, Context::Menu].iter()
+                    .any(|c| first.available_in(*c) && second.available_in(*c));
+                if !shared {
+                    continue;
+                }
+
+                for input in self.inputs(*first) {
+                    if self.inputs(*second).contains(input) {
+                        conflicts.push(Conflict { input: *input, first: *first, second: *second });
+                    }
+                }
+            }
+        }
+
+        conflicts
+    }
+
+    /// Serializes the `Keymap` in the text format described at the top of this module.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+
+        for action in Action::ALL {
+            let inputs: Vec<String> = self.inputs(action).iter().map(|i| i.name()).collect();
+            text.push_str(&format!("{} = {}\n", action.name(), inputs.join(", ")));
+        }
+
+        text
+    }
+
+    /// Parses a `Keymap` in the text format described at the top of this module.
+    pub fn parse(text: &str) -> Result<Keymap, String> {
+        let mut keymap = Keymap::new();
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (name, inputs) = line.split_once('=')
+                .ok_or_else(|| format!("line {}: expected 'action = inputs'", i + 1))?;
+
+            let action = Action::from_name(name.trim())
+                .ok_or_else(|| format!("line {}: unknown action '{}'", i + 1, name.trim()))?;
+
+            let inputs = inputs.split(',')
+                .map(str::trim)
+                .filter(|k| !k.is_empty())
+                .map(|k| Input::from_name(k).ok_or_else(|| format!("line {}: unknown key or button '{}'", i + 1, k)))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            keymap.bind(action, inputs);
+        }
+
+        Ok(keymap)
+    }
+
+    pub fn load(path: &Path) -> Result<Keymap, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("could not read {}: {}", path.display(), e))?;
+
+        Keymap::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        fs::write(path, self.to_text())
+    }
+}
+
+
+impl Default for Keymap {
+    fn default() -> Keymap {
+        Keymap::new()
+    }
+}
+
+
+/// Tracks the left stick of a controller, so it steers like the D-pad: pushing the stick past
+/// the dead zone presses the D-pad button of that direction, once, until the stick is pushed
+/// some other way.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct Stick {
+    x           : i16,
+    y           : i16,
+    /// The D-pad button the stick is holding down.
+    pressed     : Option<Button>,
+}
+
+
+impl Stick {
+
+    pub fn new() -> Stick {
+        Stick::default()
+    }
+
+    /// Moves the stick along `axis` to `value`. Returns the D-pad button this presses, if the
+    /// stick was not already pushed that way. Axes other than those of the left stick are ignored.
+    pub fn move_axis(&mut self, axis: Axis, value: i16) -> Option<Button> {
+        match axis {
+            Axis::LeftX => self.x = value,
+            Axis::LeftY => self.y = value,
+            _ => return None,
+        }
+
+        // The direction the stick is pushed the most wins.
+        let (x, y) = (self.x.saturating_abs(), self.y.saturating_abs());
+        let pushed = if x.max(y) < STICK_DEAD_ZONE {
+            None
+        } else if x >= y {
+            Some(if self.x < 0 { Button::DPadLeft } else { Button::DPadRight })
+        } else {
+            Some(if self.y < 0 { Button::DPadUp } else { Button::DPadDown })
+        };
+
+        if pushed == self.pressed {
+            return None;
+        }
+
+        self.pressed = pushed;
+        pushed
+    }
+}
+
+
+/// How a player steers their `Snake` in a versus game. Every player needs inputs of their own,
+/// so these are fixed rather than taken from the `Keymap`.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Steering {
+    Arrows,
+    Wasd,
+    /// The D-pad (or left stick) of the controller at this index, in the order they were
+    /// plugged in.
+    Controller(usize),
+}
+
+
+impl Steering {
+
+    /// How the player at `index` steers: the arrows, then W A S D, then each controller.
+    pub fn of_player(index: usize) -> Steering {
+        match index {
+            0 => Steering::Arrows,
+            1 => Steering::Wasd,
+            n => Steering::Controller(n - 2),
+        }
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            Steering::Arrows => String::from("Arrows"),
+            Steering::Wasd => String::from("WASD"),
+            Steering::Controller(n) => format!("Controller {}", n + 1),
+        }
+    }
+
+    /// The direction `input` steers to, if it is one of the inputs of this steering.
+    /// `controller` is the index of the controller a button was pressed on.
+    pub fn turn(self, input: Input, controller: Option<usize>) -> Option<Direction> {
+        match (self, input) {
+            (Steering::Arrows, Input::Key(key)) => match key {
+                Keycode::Left  => Some(Direction::LEFT),
+                Keycode::Right => Some(Direction::RIGHT),
+                Keycode::Up    => Some(Direction::UP),
+                Keycode::Down  => Some(Direction::DOWN),
+                _ => None,
+            },
+            (Steering::Wasd, Input::Key(key)) => match key {
+                Keycode::A => Some(Direction::LEFT),
+                Keycode::D => Some(Direction::RIGHT),
+                Keycode::W => Some(Direction::UP),
+                Keycode::S => Some(Direction::DOWN),
+                _ => None,
+            },
+            (Steering::Controller(n), Input::Pad(button)) if controller == Some(n) => match button {
+                Button::DPadLeft  => Some(Direction::LEFT),
+                Button::DPadRight => Some(Direction::RIGHT),
+                Button::DPadUp    => Some(Direction::UP),
+                Button::DPadDown  => Some(Direction::DOWN),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
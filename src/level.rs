@@ -0,0 +1,291 @@
+//! Levels: the layout of the `GameArea`, with internal walls, where the `Snake` starts and,
+//! optionally, the only places where food may appear.
+//!
+//! Levels are plain text files. A few `key: value` header lines come first, followed by the
+//! grid, one line per row of cells:
+//!
+//! ```text
+//! name: Pillars
+//! direction: right
+//! length: 5
+//! target: 10
+//!
+//! ..........
+//! ..##...F..
+//! ....S.....
+//! ..........
+//! ```
+//!
+//! In the grid, `.` is an empty cell, `#` a wall, `S` the head of the `Snake` and `F` a spot
+//! where food may appear. If there is no `F` at all, food can appear on any free cell. All the
+//! rows must have the same number of cells, and there can be no blank line within the grid. The
+//! body of the `Snake` extends from `S` in the opposite `direction`, so those cells must be free.
+//! The `target` is the score needed to clear the level in a campaign.
+//! All the header lines are optional; by default the `Snake` heads right with 5 cells and the
+//! target is 10.
+
+use std::fs;
+use std::path::Path;
+
+use crate::sim::{self, Coordinate, Direction};
+
+
+/// The levels shipped with the game, from `res/levels/`, in the order a campaign plays them.
+const BUNDLED: &[&str] = &[
+    include_str!("../res/levels/01-pillars.txt"),
+    include_str!("../res/levels/02-box.txt"),
+    include_str!("../res/levels/03-cross.txt"),
+    include_str!("../res/levels/04-corridors.txt"),
+    include_str!("../res/levels/05-rooms.txt"),
+];
+
+const DEFAULT_LENGTH: u32 = 5;
+const DEFAULT_TARGET: u32 = 10;
+
+
+/// The layout of a `GameArea` of `hcells` x `vcells` cells.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Level {
+    pub name        : String,
+    pub hcells      : u32,
+    pub vcells      : u32,
+    pub walls       : Vec<Coordinate>,
+    /// Where the head of the `Snake` starts.
+    pub spawn       : Coordinate,
+    /// Where the `Snake` heads at the start.
+    pub direction   : Direction,
+    /// Number of cells of the `Snake` at the start.
+    pub length      : u32,
+    /// The only cells where food may appear. If empty, food may appear on any free cell.
+    pub food_spots  : Vec<Coordinate>,
+    /// Score needed to clear the level in a campaign.
+    pub target      : u32,
+}
+
+
+impl Level {
+
+    /// A level without any internal wall: the `Snake` starts in the middle, heading right.
+    pub fn open(hcells: u32, vcells: u32) -> Level {
+        Level {
+            name        : String::from("Open"),
+            hcells,
+            vcells,
+            walls       : Vec::new(),
+            spawn       : Coordinate { x: hcells / 2, y: vcells / 2 },
+            direction   : Direction::RIGHT,
+            length      : DEFAULT_LENGTH,
+            food_spots  : Vec::new(),
+            target      : DEFAULT_TARGET,
+        }
+    }
+
+    /// The levels shipped with the game.
+    pub fn bundled() -> Vec<Level> {
+        BUNDLED.iter()
+            .map(|text| Level::parse(text).expect("bundled levels are valid"))
+            .collect()
+    }
+
+    /// Finds a level by name among the ones shipped with the game, ignoring case.
+    pub fn find_bundled(name: &str) -> Option<Level> {
+        Level::bundled().into_iter().find(|level| level.name.eq_ignore_ascii_case(name))
+    }
+
+    /// The cells of the `Snake` at the start, head first.
+    pub fn snake_body(&self) -> Vec<Coordinate> {
+        let mut body = vec![self.spawn];
+
+        for _ in 1..self.length {
+            let last = *body.last().unwrap();
+            let next = match self.direction {
+                Direction::LEFT  => Coordinate { x: last.x + 1, y: last.y },
+                Direction::RIGHT => Coordinate { x: last.x.wrapping_sub(1), y: last.y },
+                Direction::UP    => Coordinate { x: last.x, y: last.y + 1 },
+                Direction::DOWN  => Coordinate { x: last.x, y: last.y.wrapping_sub(1) },
+            };
+            body.push(next);
+        }
+
+        body
+    }
+
+    /// Checks the `Snake` fits in the level: its body must be inside the area and clear of walls.
+    /// The area may not have more than `MAX_CELLS` cells.
+    pub fn validate(&self) -> Result<(), String> {
+        let cells = sim::area(self.hcells, self.vcells)?;
+
+        if self.length == 0 {
+            return Err(String::from("the snake needs at least one cell"));
+        }
+
+        // Checked before building the body, which would take forever for absurd lengths.
+        if self.length as usize > cells {
+            return Err(format!("the snake has {} cells, more than the whole area", self.length));
+        }
+
+        if self.target == 0 {
+            return Err(String::from("the target score must be at least 1"));
+        }
+
+        for cell in self.snake_body() {
+            if cell.x >= self.hcells || cell.y >= self.vcells {
+                return Err(String::from("the snake does not fit between its spawn point and the edge"));
+            }
+
+            if self.walls.contains(&cell) {
+                return Err(format!("the snake starts on a wall at ({}, {})", cell.x, cell.y));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Parses a `Level` in the text format described at the top of this module.
+    pub fn parse(text: &str) -> Result<Level, String> {
+        let mut name      = String::from("Unnamed");
+        let mut direction = Direction::RIGHT;
+        let mut length    = DEFAULT_LENGTH;
+        let mut target    = DEFAULT_TARGET;
+        let mut rows      = Vec::new();
+
+        for (i, row) in text.lines().enumerate() {
+            let line = row.trim_end();
+
+            if rows.is_empty() {
+                if line.is_empty() {
+                    continue;
+                }
+
+                if let Some((key, value)) = line.split_once(':') {
+                    let value = value.trim();
+                    match key.trim() {
+                        "name" => {
+                            name = String::from(value);
+                        },
+                        "direction" => {
+                            direction = Direction::from_name(value)
+                                .ok_or_else(|| format!("line {}: invalid direction '{}'", i + 1, value))?;
+                        },
+                        "length" => {
+                            length = value.parse()
+                                .map_err(|_| format!("line {}: invalid length '{}'", i + 1, value))?;
+                        },
+                        "target" => {
+                            target = value.parse()
+                                .map_err(|_| format!("line {}: invalid target '{}'", i + 1, value))?;
+                        },
+                        key => return Err(format!("line {}: unknown key '{}'", i + 1, key)),
+                    }
+                    continue;
+                }
+            }
+
+            rows.push((i + 1, row));
+        }
+
+        // Blank lines after the grid are fine, but not within it.
+        while rows.last().is_some_and(|(_, row)| row.trim().is_empty()) {
+            rows.pop();
+        }
+
+        if rows.is_empty() {
+            return Err(String::from("the level has no grid"));
+        }
+
+        let width = rows[0].1.chars().count();
+        for (line, row) in &rows {
+            if row.trim().is_empty() {
+                return Err(format!("line {}: blank line in the grid", line));
+            }
+
+            if row.chars().count() != width {
+                return Err(format!("line {}: the row has {} cells, but the first one has {}",
+                                   line, row.chars().count(), width));
+            }
+        }
+
+        let mut walls      = Vec::new();
+        let mut food_spots = Vec::new();
+        let mut spawn      = None;
+
+        for (y, (line, row)) in rows.iter().enumerate() {
+            for (x, cell) in row.chars().enumerate() {
+                let coord = Coordinate { x: x as u32, y: y as u32 };
+
+                match cell {
+                    '.' | ' ' => {},
+                    '#' => walls.push(coord),
+                    'F' => food_spots.push(coord),
+                    'S' => {
+                        if spawn.replace(coord).is_some() {
+                            return Err(format!("line {}: more than one spawn point", line));
+                        }
+                    },
+                    c => return Err(format!("line {}: unexpected '{}' in the grid", line, c)),
+                }
+            }
+        }
+
+        let level = Level {
+            name,
+            hcells: width as u32,
+            vcells: rows.len() as u32,
+            walls,
+            spawn: spawn.ok_or("the level has no spawn point 'S'")?,
+            direction,
+            length,
+            food_spots,
+            target,
+        };
+
+        level.validate()?;
+        Ok(level)
+    }
+
+    pub fn load(path: &Path) -> Result<Level, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("could not read {}: {}", path.display(), e))?;
+
+        Level::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Loads the level in the file at `name` or, if there is no such file, the bundled level
+    /// called `name`.
+    pub fn find(name: &str) -> Result<Level, String> {
+        let path = Path::new(name);
+        if path.exists() {
+            return Level::load(path);
+        }
+
+        Level::find_bundled(name).ok_or_else(|| {
+            let names: Vec<String> = Level::bundled().into_iter().map(|level| level.name).collect();
+            format!("no level file '{}', nor a bundled level by that name (bundled levels: {})",
+                    name, names.join(", "))
+        })
+    }
+
+    /// Serializes the `Level` in the text format described at the top of this module.
+    pub fn to_text(&self) -> String {
+        let mut text = format!("name: {}\n", self.name);
+        text.push_str(&format!("direction: {}\n", self.direction.name().to_lowercase()));
+        text.push_str(&format!("length: {}\n", self.length));
+        text.push_str(&format!("target: {}\n\n", self.target));
+
+        let mut grid = vec![vec!['.'; self.hcells as usize]; self.vcells as usize];
+        for wall in &self.walls {
+            grid[wall.y as usize][wall.x as usize] = '#';
+        }
+        for spot in &self.food_spots {
+            grid[spot.y as usize][spot.x as usize] = 'F';
+        }
+        grid[self.spawn.y as usize][self.spawn.x as usize] = 'S';
+
+        for row in grid {
+            text.extend(row);
+            text.push('\n');
+        }
+
+        text
+    }
+}
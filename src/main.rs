@@ -1,11 +1,94 @@
+use std::path::{Path, PathBuf};
 use std::process;
+use std::time::Duration;
+
+use snake_rust::ai::Strategy;
+use snake_rust::bench::{self, BenchConfig};
+use snake_rust::cli::parse_value;
+use snake_rust::config::{Difficulty, GameConfig};
+use snake_rust::level::Level;
+use snake_rust::replay::Replay;
+use snake_rust::sim::BoardMode;
+
+const USAGE: &str = "\
+Usage: snake-rust [OPTIONS]
+
+Options:
+    --font <FILE>       Font used to render text, instead of looking for Roboto
+    --seed <NUMBER>     Seed for every game, to make them reproducible
+    --width <CELLS>     Width of the board, in cells
+    --height <CELLS>    Height of the board, in cells
+    --cell-size <PX>    Size of a cell, in pixels
+    --speed <MS>        Time between two ticks of the game, in milliseconds
+    --wrap              Leaving the board on one side enters it on the opposite side
+    --difficulty <NAME> easy, normal or hard: how fast the game goes, relative to --speed
+    --players <N>       Number of snakes, from 1 to 4; with more than one, players race each
+                        other on the same board (arrows, WASD, then controllers)
+    --opponents <N>     Number of computer snakes joining the players; how well they play
+                        depends on --difficulty
+    --bot <NAME>        Let a built-in bot play instead of you: greedy, astar or hamiltonian
+    --level <LEVEL>     Play on a level file, or on one of the bundled levels by name; the
+                        board then has the size of the level
+    --replay <FILE>     Play back a recorded game
+    --headless          Don't open a window; with --replay, check the recorded game still
+                        plays the same, or with --bot, let the bot play one game and print
+                        how it went
+    -h, --help          Print this help
+    -V, --version       Print the version
+";
+
+/// What the user asked for on the command line.
+enum Command {
+    /// Play the game (or watch a replay) with the given configuration.
+    Play(GameConfig),
+    /// Play a replay without a display, checking it still ends as recorded.
+    Verify(Replay),
+    /// Let a bot play one game without a display, on the board and from the seed given.
+    Simulate(BenchConfig, Strategy),
+    Help,
+    Version,
+}
 
 /// Entry point.
 fn main() {
-    let font_path = get_font_path();
+    let command = match parse_args(std::env::args().skip(1)) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("snake-rust: {}", e);
+            eprintln!("Try 'snake-rust --help' for more information.");
+            process::exit(2);
+        }
+    };
+
+    let result = match command {
+        Command::Play(config) => snake_rust::run(&config).map_err(|e| e.to_string()),
+        Command::Verify(replay) => {
+            replay.verify()
+                .map(|_| println!("The replay plays as recorded."))
+        },
+        Command::Simulate(config, strategy) => {
+            bench::play_game(&config, strategy, config.seed).map(|game| {
+                let end = match game.collision {
+                    Some(cause) => format!("then hit the {}", bench::cause_name(cause)),
+                    None if game.won => String::from("and filled the board"),
+                    None => String::from("and was stopped, still alive"),
+                };
+                println!("{} (seed {}) scored {} in {} ticks, {}.",
+                         strategy.name(), game.seed, game.score, game.ticks, end);
+            })
+        },
+        Command::Help => {
+            print!("{}", USAGE);
+            Ok(())
+        },
+        Command::Version => {
+            println!("snake-rust {}", env!("CARGO_PKG_VERSION"));
+            Ok(())
+        },
+    };
 
-	if let Err(e) = snake_rust::run(&font_path) {
-        eprintln!("{:?}", e);
+	if let Err(e) = result {
+        eprintln!("snake-rust: {}", e);
         process::exit(1);
     }
     else {
@@ -13,22 +96,103 @@ fn main() {
     }
 }
 
-/// Returns the path of the font file we are going to use for rendering text in SDL.
-/// FIXME: Return some kind of "Path" object instead of a String.
-/// TODO:  Validate the path actually exists.
+/// Parses the command line `args` (without the program name).
 ///
-fn get_font_path() -> String {
-
-    if let Some(project_root) = option_env!("CARGO_MANIFEST_DIR") {
-        // FIXME: this only works in the development environment,
-        // we should make the font file part of the distribution.
-        let mut path = String::from(project_root);
-        path.push_str("/res/Roboto-Regular.ttf");
-        path
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Command, String> {
+    let mut config = GameConfig::new();
+    let mut headless = false;
+    let mut sized = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--font" => {
+                let path = args.next().ok_or("missing value for --font")?;
+                config.font_path = Some(PathBuf::from(path));
+            },
+            "--seed" => {
+                config.seed = Some(parse_value(&arg, args.next())?);
+            },
+            "--width" => {
+                config.hcells = parse_value(&arg, args.next())?;
+                sized = true;
+            },
+            "--height" => {
+                config.vcells = parse_value(&arg, args.next())?;
+                sized = true;
+            },
+            "--cell-size" => {
+                config.cell_size = parse_value(&arg, args.next())?;
+            },
+            "--speed" => {
+                config.speed = Duration::from_millis(parse_value(&arg, args.next())?);
+            },
+            "--wrap" => {
+                config.mode = BoardMode::WRAP;
+            },
+            "--difficulty" => {
+                let name = args.next().ok_or("missing value for --difficulty")?;
+                config.difficulty = Difficulty::from_name(&name)
+                    .ok_or_else(|| format!("invalid value '{}' for --difficulty: expected easy, normal or hard", name))?;
+            },
+            "--players" => {
+                config.players = parse_value(&arg, args.next())?;
+            },
+            "--opponents" => {
+                config.opponents = parse_value(&arg, args.next())?;
+            },
+            "--bot" => {
+                let name = args.next().ok_or("missing value for --bot")?;
+                config.bot = Some(Strategy::from_name(&name)
+                    .ok_or_else(|| format!("invalid value '{}' for --bot: expected greedy, astar or hamiltonian", name))?);
+            },
+            "--level" => {
+                let level = Level::find(&args.next().ok_or("missing value for --level")?)?;
+                config.hcells = level.hcells;
+                config.vcells = level.vcells;
+                config.level = Some(level);
+            },
+            "--replay" => {
+                let path = args.next().ok_or("missing value for --replay")?;
+                config.replay = Some(Replay::load(Path::new(&path))?);
+            },
+            "--headless" => {
+                headless = true;
+            },
+            "-h" | "--help" => {
+                return Ok(Command::Help);
+            },
+            "-V" | "--version" => {
+                return Ok(Command::Version);
+            },
+            _ => {
+                return Err(format!("unknown option '{}'", arg));
+            },
+        }
     }
-    else {
-        // As a fallback, use the system-wide path for the font in the Debian package `fonts-roboto`:
-        String::from("/usr/share/fonts/truetype/roboto/unhinted/RobotoTTF/Roboto-Regular.ttf")
+
+    if sized && config.level.is_some() {
+        return Err(String::from("--level sets the size of the board, so it cannot be used with --width or --height"));
     }
-}
 
+    config.validate()?;
+
+    if headless {
+        return match (config.replay, config.bot) {
+            (Some(replay), _) => Ok(Command::Verify(replay)),
+            (None, Some(_)) if config.players > 1 || config.opponents > 0 => {
+                Err(String::from("--headless with --bot plays a single snake, without --players or --opponents"))
+            },
+            (None, Some(strategy)) => {
+                let level = config.level.unwrap_or_else(|| Level::open(config.hcells, config.vcells));
+                let mut bench = BenchConfig::new(level.hcells, level.vcells);
+                bench.level = level;
+                bench.mode = config.mode;
+                bench.seed = config.seed.unwrap_or_else(rand::random);
+                Ok(Command::Simulate(bench, strategy))
+            },
+            (None, None) => Err(String::from("--headless needs a game to play, with --replay or --bot")),
+        };
+    }
+
+    Ok(Command::Play(config))
+}
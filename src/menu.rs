@@ -0,0 +1,253 @@
+//! A menu made of entries the player moves through: plain actions, values to pick from a list,
+//! and submenus. The `Menu` only keeps track of what is selected; drawing it and turning keys or
+//! clicks into calls to its methods is up to the `Game`.
+
+use crate::keymap::Action;
+
+
+/// Identifies what an entry of a `Menu` is about, so the `Game` knows what to do when it is
+/// selected or its value changes.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum MenuId {
+    NEWGAME,
+    CAMPAIGN,
+    MODE,
+    DIFFICULTY,
+    PLAYERS,
+    OPPONENTS,
+    LEVEL,
+    GRID,
+    HIGHSCORES,
+    CONTROLS,
+    EXIT,
+    RESUME,
+    RESTART,
+    /// Leave the game for the main menu.
+    QUIT,
+    /// Pick a new key for an `Action`.
+    BIND(Action),
+    /// Go back to the default keys.
+    DEFAULTS,
+}
+
+
+/// The different kinds of entries.
+#[derive(Debug, Clone)]
+enum EntryKind {
+    /// Selecting the entry does whatever its `MenuId` stands for.
+    ACTION(MenuId),
+    /// A value picked among `values`, cycled through with left and right.
+    SELECTOR { id: MenuId, values: Vec<String>, selected: usize },
+    /// Selecting the entry opens the page at this index.
+    SUBMENU(usize),
+    /// Selecting the entry goes back to the previous page.
+    BACK,
+}
+
+
+#[derive(Debug, Clone)]
+struct Entry {
+    label       : String,
+    kind        : EntryKind,
+}
+
+
+/// A list of entries shown at once.
+#[derive(Debug, Clone)]
+struct Page {
+    title       : String,
+    entries     : Vec<Entry>,
+    /// Index of the highlighted entry.
+    current     : usize,
+}
+
+
+/// What the player did to the `Menu`.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum MenuEvent {
+    /// Nothing the `Game` has to act on, e.g. the highlighted entry moved.
+    NONE,
+    /// An action entry was selected.
+    SELECTED(MenuId),
+    /// The value of a selector changed to the one at this index.
+    CHANGED(MenuId, usize),
+    /// The player went back from the first page, leaving the menu.
+    CLOSED,
+}
+
+
+/// A menu, with its submenus. The first page is the main one; the others are reached through
+/// submenu entries.
+#[derive(Debug, Clone)]
+pub struct Menu {
+    pages       : Vec<Page>,
+    /// Pages open, from the main one to the one shown.
+    open        : Vec<usize>,
+    /// While building the `Menu`, the page new entries go to is the last one here.
+    building    : Vec<usize>,
+}
+
+
+impl Menu {
+
+    /// Create a `Menu` whose main page has this `title` and no entries yet.
+    pub fn new(title: &str) -> Menu {
+        Menu {
+            pages   : vec![Page { title: String::from(title), entries: Vec::new(), current: 0 }],
+            open    : vec![0],
+            building: vec![0],
+        }
+    }
+
+    fn add(&mut self, label: &str, kind: EntryKind) -> &mut Menu {
+        let page = *self.building.last().unwrap();
+        self.pages[page].entries.push(Entry { label: String::from(label), kind });
+        self
+    }
+
+    /// Adds an entry that does `id` when selected.
+    pub fn action(&mut self, label: &str, id: MenuId) -> &mut Menu {
+        self.add(label, EntryKind::ACTION(id))
+    }
+
+    /// Adds an entry to pick one of `values`, the one at `selected` to begin with.
+    pub fn selector(&mut self, label: &str, id: MenuId, values: Vec<String>, selected: usize) -> &mut Menu {
+        let selected = selected.min(values.len().saturating_sub(1));
+        self.add(label, EntryKind::SELECTOR { id, values, selected })
+    }
+
+    /// Adds an entry opening a submenu. Entries added from now on go to the submenu, until
+    /// `end_submenu`.
+    pub fn submenu(&mut self, label: &str) -> &mut Menu {
+        let index = self.pages.len();
+        self.add(label, EntryKind::SUBMENU(index));
+        self.pages.push(Page { title: String::from(label), entries: Vec::new(), current: 0 });
+        self.building.push(index);
+        self
+    }
+
+    /// Adds an entry to go back to the previous page or, from the main one, to leave the menu.
+    pub fn back(&mut self) -> &mut Menu {
+        self.add("Back", EntryKind::BACK)
+    }
+
+    /// Ends the submenu being built with an entry to go back. Entries added from now on go to
+    /// the page the submenu was opened from.
+    pub fn end_submenu(&mut self) -> &mut Menu {
+        self.back();
+        if self.building.len() > 1 {
+            self.building.pop();
+        }
+        self
+    }
+
+    fn page(&self) -> &Page {
+        &self.pages[*self.open.last().unwrap()]
+    }
+
+    fn page_mut(&mut self) -> &mut Page {
+        &mut self.pages[*self.open.last().unwrap()]
+    }
+
+    /// Title of the page shown.
+    pub fn title(&self) -> &str {
+        &self.page().title
+    }
+
+    /// Labels of the entries of the page shown, with the value of selectors.
+    pub fn labels(&self) -> Vec<String> {
+        self.page().entries.iter()
+            .map(|entry| match &entry.kind {
+                EntryKind::SELECTOR { values, selected, .. } => format!("{}: < {} >", entry.label, values[*selected]),
+                _ => entry.label.clone(),
+            })
+            .collect()
+    }
+
+    /// Index of the highlighted entry of the page shown.
+    pub fn current(&self) -> usize {
+        self.page().current
+    }
+
+    /// Whether the page shown is the main one.
+    pub fn at_top(&self) -> bool {
+        self.open.len() == 1
+    }
+
+    /// Highlights the previous entry, or the last one from the first.
+    pub fn up(&mut self) {
+        let page = self.page_mut();
+        if page.entries.is_empty() {
+            return;
+        }
+
+        page.current = (page.current + page.entries.len() - 1) % page.entries.len();
+    }
+
+    /// Highlights the next entry, or the first one from the last.
+    pub fn down(&mut self) {
+        let page = self.page_mut();
+        if page.entries.is_empty() {
+            return;
+        }
+
+        page.current = (page.current + 1) % page.entries.len();
+    }
+
+    /// Highlights the entry at `index`, e.g. the one under the mouse. Returns whether there is
+    /// such entry.
+    pub fn hover(&mut self, index: usize) -> bool {
+        let page = self.page_mut();
+        if index >= page.entries.len() {
+            return false;
+        }
+
+        page.current = index;
+        true
+    }
+
+    /// Changes the value of the highlighted entry, if it is a selector, to the previous
+    /// (`step` < 0) or next value.
+    pub fn change(&mut self, step: i32) -> MenuEvent {
+        let page = self.page_mut();
+        let current = page.current;
+
+        match page.entries.get_mut(current).map(|entry| &mut entry.kind) {
+            Some(EntryKind::SELECTOR { id, values, selected }) if !values.is_empty() => {
+                let count = values.len() as i32;
+                *selected = (*selected as i32 + step).rem_euclid(count) as usize;
+                MenuEvent::CHANGED(*id, *selected)
+            },
+            _ => MenuEvent::NONE,
+        }
+    }
+
+    /// Selects the highlighted entry: actions are reported, selectors move to their next value
+    /// and submenus open. Nothing happens on a page without entries.
+    pub fn select(&mut self) -> MenuEvent {
+        let page = self.page();
+
+        match page.entries.get(page.current).map(|entry| &entry.kind) {
+            Some(EntryKind::ACTION(id)) => MenuEvent::SELECTED(*id),
+            Some(EntryKind::SELECTOR { .. }) => self.change(1),
+            Some(EntryKind::SUBMENU(index)) => {
+                let index = *index;
+                self.pages[index].current = 0;
+                self.open.push(index);
+                MenuEvent::NONE
+            },
+            Some(EntryKind::BACK) => self.back_out(),
+            None => MenuEvent::NONE,
+        }
+    }
+
+    /// Goes back to the previous page or, from the main one, leaves the menu.
+    pub fn back_out(&mut self) -> MenuEvent {
+        if self.at_top() {
+            return MenuEvent::CLOSED;
+        }
+
+        self.open.pop();
+        MenuEvent::NONE
+    }
+}
@@ -0,0 +1,61 @@
+//! Locations of the files the game reads and writes, following the XDG base directory
+//! specification.
+
+use std::env;
+use std::path::PathBuf;
+
+
+const APP_DIR: &str = "snake-rust";
+
+
+/// Returns `$<var>` if it is set to an absolute path; otherwise `$HOME/<fallback>`.
+fn xdg_dir(var: &str, fallback: &str) -> Option<PathBuf> {
+    if let Some(dir) = env::var_os(var).map(PathBuf::from) {
+        if dir.is_absolute() {
+            return Some(dir);
+        }
+    }
+
+    home_dir().map(|home| home.join(fallback))
+}
+
+
+pub fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME").map(PathBuf::from)
+}
+
+
+/// Base directory for user data files: `$XDG_DATA_HOME`, which defaults to `~/.local/share`.
+pub fn data_home() -> Option<PathBuf> {
+    xdg_dir("XDG_DATA_HOME", ".local/share")
+}
+
+
+/// Directory where the game keeps the data it generates, such as replays:
+/// `$XDG_DATA_HOME/snake-rust`, which defaults to `~/.local/share/snake-rust`.
+pub fn data_dir() -> Option<PathBuf> {
+    data_home().map(|dir| dir.join(APP_DIR))
+}
+
+
+/// Directory where the user can put files to customize the game:
+/// `$XDG_CONFIG_HOME/snake-rust`, which defaults to `~/.config/snake-rust`.
+pub fn config_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_CONFIG_HOME", ".config").map(|dir| dir.join(APP_DIR))
+}
+
+
+/// System-wide base directories for data files, most important first: `$XDG_DATA_DIRS`, which
+/// defaults to `/usr/local/share:/usr/share`.
+pub fn data_dirs() -> Vec<PathBuf> {
+    let dirs = env::var_os("XDG_DATA_DIRS")
+        .map(|dirs| env::split_paths(&dirs).filter(|d| d.is_absolute()).collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    if dirs.is_empty() {
+        vec![PathBuf::from("/usr/local/share"), PathBuf::from("/usr/share")]
+    }
+    else {
+        dirs
+    }
+}
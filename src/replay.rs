@@ -0,0 +1,293 @@
+//! Recording and playback of games. Given the seed and the board size, the direction changes
+//! and the tick on which each of them was applied are enough to play a game again, exactly as it
+//! happened the first time.
+//!
+//! Replays are saved as plain text:
+//!
+//! ```text
+//! snake-rust-replay 1
+//! version 0.1.0
+//! seed 42
+//! board 38 28
+//! mode WALLS
+//! level name: Pillars
+//! level ...
+//! turn 3 UP
+//! turn 9 LEFT
+//! end 120 4
+//! ```
+//!
+//! Games played on a `Level` other than the open board embed the whole level, one `level` line
+//! per line of the level file. The `end` line holds the number of ticks played and the final
+//! score, so a replay can check that the rules still produce the same game.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::level::Level;
+use crate::sim::{BoardMode, Direction, Simulation, StepOutcome};
+
+
+/// Version of the replay format. Bump it whenever the format (or the rules of the game!) change
+/// in a way that makes older replays play differently.
+const FORMAT_VERSION: u32 = 1;
+const MAGIC: &str = "snake-rust-replay";
+
+
+/// A recorded game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replay {
+    pub seed        : u64,
+    pub hcells      : u32,
+    pub vcells      : u32,
+    pub mode        : BoardMode,
+    /// The level the game was played on, unless it was the open board.
+    pub level       : Option<Level>,
+    /// The direction changes of the game, along with the tick they were applied on, in order.
+    pub turns       : Vec<(u64, Direction)>,
+    /// Number of ticks played and final score, once the game is over.
+    pub end         : Option<(u64, u32)>,
+}
+
+
+/// Parses the whitespace-separated `fields` of `line` as numbers.
+fn parse_numbers<T: std::str::FromStr>(line: usize, fields: &[&str]) -> Result<Vec<T>, String> {
+    fields.iter()
+        .map(|f| f.parse().map_err(|_| format!("line {}: invalid number '{}'", line, f)))
+        .collect()
+}
+
+
+impl Replay {
+
+    /// Start recording a game played on a `hcells` x `vcells` area in the given `mode`, with the
+    /// given `seed`.
+    pub fn new(seed: u64, hcells: u32, vcells: u32, mode: BoardMode) -> Replay {
+        Replay {
+            seed,
+            hcells,
+            vcells,
+            mode,
+            level   : None,
+            turns   : Vec::new(),
+            end     : None,
+        }
+    }
+
+    /// Start recording the game `sim` is about to play.
+    pub fn for_simulation(sim: &Simulation) -> Replay {
+        let mut replay = Replay::new(sim.seed(), sim.hcells(), sim.vcells(), sim.mode());
+
+        if *sim.level() != Level::open(sim.hcells(), sim.vcells()) {
+            replay.level = Some(sim.level().clone());
+        }
+
+        replay
+    }
+
+    /// Records that the `Snake` turned to `direction` on `tick`.
+    pub fn record(&mut self, tick: u64, direction: Direction) {
+        self.turns.push((tick, direction));
+    }
+
+    /// Records the end of the game.
+    pub fn finish(&mut self, ticks: u64, score: u32) {
+        self.end = Some((ticks, score));
+    }
+
+    /// Creates a new `Simulation` at the start of the recorded game. Fails if the `Snake` does
+    /// not fit on the recorded board.
+    pub fn simulation(&self) -> Result<Simulation, String> {
+        match &self.level {
+            Some(level) => Simulation::from_level(level.clone(), self.mode, self.seed),
+            None => Simulation::new(self.hcells, self.vcells, self.mode, self.seed),
+        }
+    }
+
+    /// Serializes the `Replay` in the text format described at the top of this module.
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{} {}\n", MAGIC, FORMAT_VERSION);
+        text.push_str(&format!("version {}\n", env!("CARGO_PKG_VERSION")));
+        text.push_str(&format!("seed {}\n", self.seed));
+        text.push_str(&format!("board {} {}\n", self.hcells, self.vcells));
+        text.push_str(&format!("mode {}\n", self.mode.name()));
+
+        if let Some(level) = &self.level {
+            for line in level.to_text().lines() {
+                text.push_str(format!("level {}", line).trim_end());
+                text.push('\n');
+            }
+        }
+
+        for (tick, direction) in &self.turns {
+            text.push_str(&format!("turn {} {}\n", tick, direction.name()));
+        }
+
+        if let Some((ticks, score)) = self.end {
+            text.push_str(&format!("end {} {}\n", ticks, score));
+        }
+
+        text
+    }
+
+    /// Parses a `Replay` in the text format described at the top of this module.
+    pub fn parse(text: &str) -> Result<Replay, String> {
+        let mut lines = text.lines().enumerate().map(|(i, l)| (i + 1, l));
+
+        match lines.next().map(|(_, l)| l.split_whitespace().collect::<Vec<_>>()) {
+            Some(header) if header.len() == 2 && header[0] == MAGIC => {
+                if header[1] != FORMAT_VERSION.to_string() {
+                    return Err(format!("unsupported replay format version {}", header[1]));
+                }
+            },
+            _ => return Err(String::from("not a replay file")),
+        }
+
+        let mut seed  = None;
+        let mut board = None;
+        let mut mode  = BoardMode::WALLS;
+        let mut turns = Vec::new();
+        let mut end   = None;
+        let mut level = None::<String>;
+
+        for (line, raw) in lines {
+            // Level lines are kept verbatim: they are parsed as a whole once they are all read.
+            if let Some(rest) = raw.strip_prefix("level") {
+                if rest.is_empty() || rest.starts_with(' ') {
+                    let level = level.get_or_insert_with(String::new);
+                    level.push_str(rest.strip_prefix(' ').unwrap_or(rest));
+                    level.push('\n');
+                    continue;
+                }
+            }
+
+            let fields = raw.split_whitespace().collect::<Vec<_>>();
+            match fields.as_slice() {
+                [] | ["version", _] => {},
+                ["seed", value] => {
+                    seed = Some(parse_numbers::<u64>(line, &[value])?[0]);
+                },
+                ["board", h, v] => {
+                    let size = parse_numbers::<u32>(line, &[h, v])?;
+                    board = Some((line, size[0], size[1]));
+                },
+                ["mode", name] => {
+                    mode = BoardMode::from_name(name)
+                        .ok_or_else(|| format!("line {}: invalid mode '{}'", line, name))?;
+                },
+                ["turn", tick, direction] => {
+                    let tick = parse_numbers::<u64>(line, &[tick])?[0];
+                    let direction = Direction::from_name(direction)
+                        .ok_or_else(|| format!("line {}: invalid direction '{}'", line, direction))?;
+
+                    if turns.last().is_some_and(|(last, _)| *last >= tick) {
+                        return Err(format!("line {}: turns are not in order", line));
+                    }
+
+                    turns.push((tick, direction));
+                },
+                ["end", ticks, score] => {
+                    let ticks = parse_numbers::<u64>(line, &[ticks])?[0];
+                    let score = parse_numbers::<u32>(line, &[score])?[0];
+                    end = Some((ticks, score));
+                },
+                _ => return Err(format!("line {}: unexpected '{}'", line, fields.join(" "))),
+            }
+        }
+
+        let seed = seed.ok_or("missing seed")?;
+        let (board_line, hcells, vcells) = board.ok_or("missing board size")?;
+
+        let level = match level {
+            Some(text) => {
+                let level = Level::parse(&text).map_err(|e| format!("invalid level: {}", e))?;
+                if (level.hcells, level.vcells) != (hcells, vcells) {
+                    return Err(String::from("the level does not match the board size"));
+                }
+                Some(level)
+            },
+            None => {
+                Level::open(hcells, vcells).validate()
+                    .map_err(|e| format!("line {}: a {}x{} board is too small: {}", board_line, hcells, vcells, e))?;
+                None
+            },
+        };
+
+        Ok(Replay { seed, hcells, vcells, mode, level, turns, end })
+    }
+
+    pub fn load(path: &Path) -> Result<Replay, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("could not read {}: {}", path.display(), e))?;
+
+        Replay::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        fs::write(path, self.to_text())
+    }
+
+    /// Plays the whole game without a display and checks it ends exactly as recorded.
+    pub fn verify(&self) -> Result<(), String> {
+        let (ticks, score) = self.end.ok_or("the replay has no end")?;
+        let mut sim = self.simulation()?;
+        let mut playback = Playback::new(self.clone());
+
+        while !playback.finished(&sim) {
+            let input = playback.input(&sim);
+            let outcome = sim.step(input);
+            if outcome == StepOutcome::COLLIDED || outcome == StepOutcome::WON {
+                break;
+            }
+        }
+
+        if sim.ticks() != ticks || sim.score() != score {
+            return Err(format!("the game ended after {} ticks with score {}, but the replay expects {} ticks with score {}",
+                               sim.ticks(), sim.score(), ticks, score));
+        }
+
+        Ok(())
+    }
+}
+
+
+/// Feeds the turns of a `Replay` to a `Simulation`, one tick at a time.
+pub struct Playback {
+    replay      : Replay,
+    /// Index of the next turn to apply.
+    next        : usize,
+}
+
+
+impl Playback {
+
+    pub fn new(replay: Replay) -> Playback {
+        Playback { replay, next: 0 }
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    /// The input to pass to the next `step` of `sim`.
+    pub fn input(&mut self, sim: &Simulation) -> Option<Direction> {
+        match self.replay.turns.get(self.next) {
+            Some((tick, direction)) if *tick == sim.ticks() => {
+                self.next += 1;
+                Some(*direction)
+            },
+            _ => None,
+        }
+    }
+
+    /// Whether `sim` already played every tick of the recording. This is how a game the player
+    /// quit (rather than lost) ends.
+    pub fn finished(&self, sim: &Simulation) -> bool {
+        self.replay.end.is_some_and(|(ticks, _)| sim.ticks() >= ticks)
+    }
+}
@@ -0,0 +1,259 @@
+//! High scores. The best games are kept in a separate table for every kind of board: the same
+//! score is not worth the same on a tiny board with wrap-around edges as on a big one with walls.
+//!
+//! High scores are saved as plain text. A `table` line starts the table of a kind of board, and
+//! each `entry` line below it holds the score, the length of the `Snake`, the duration of the game
+//! in milliseconds, its seed, its date (in seconds since the Unix epoch) and the player's name:
+//!
+//! ```text
+//! snake-rust-scores 1
+//! table WALLS 38 28 Pillars
+//! entry 42 47 95310 1234 1717236000 Alice
+//! entry 17 22 40200 99 1717237000 Bob
+//! ```
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::sim::{BoardMode, Simulation};
+
+
+/// Version of the high-score format.
+const FORMAT_VERSION: u32 = 1;
+const MAGIC: &str = "snake-rust-scores";
+
+/// Number of entries kept in every table.
+pub const MAX_ENTRIES: usize = 10;
+
+/// Longest name a player can enter.
+pub const MAX_NAME_LENGTH: usize = 16;
+
+
+/// The kind of board a game was played on. Each has its own table of high scores.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Board {
+    pub mode        : BoardMode,
+    pub hcells      : u32,
+    pub vcells      : u32,
+    /// Name of the `Level`.
+    pub level       : String,
+}
+
+
+impl Board {
+
+    /// The kind of board `sim` is played on.
+    pub fn of(sim: &Simulation) -> Board {
+        Board {
+            mode    : sim.mode(),
+            hcells  : sim.hcells(),
+            vcells  : sim.vcells(),
+            level   : sim.level().name.clone(),
+        }
+    }
+}
+
+
+/// A game in a table of high scores.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighScore {
+    pub name        : String,
+    pub score       : u32,
+    /// Number of cells of the `Snake` at the end of the game.
+    pub length      : u32,
+    pub duration    : Duration,
+    pub seed        : u64,
+    /// When the game was played, in seconds since the Unix epoch.
+    pub date        : u64,
+}
+
+
+impl HighScore {
+
+    /// The entry for the game `sim` just finished by `name`, after playing for `duration`.
+    pub fn new(name: &str, sim: &Simulation, duration: Duration) -> HighScore {
+        let date = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+
+        HighScore {
+            name    : name.chars().take(MAX_NAME_LENGTH).collect(),
+            score   : sim.score(),
+            length  : sim.snake().body.len() as u32,
+            duration,
+            seed    : sim.seed(),
+            date,
+        }
+    }
+}
+
+
+/// Formats `date`, in seconds since the Unix epoch, as `YYYY-MM-DD` (UTC).
+pub fn format_date(date: u64) -> String {
+    // Converts days since the epoch to a civil date, following Howard Hinnant's algorithm.
+    let days = (date / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+
+/// Formats `duration` as minutes and seconds, e.g. `2:05`.
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+
+/// The tables of high scores of every kind of board.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HighScores {
+    /// Entries of every table, best first.
+    tables      : Vec<(Board, Vec<HighScore>)>,
+}
+
+
+impl HighScores {
+
+    /// No high scores at all.
+    pub fn new() -> HighScores {
+        HighScores { tables: Vec::new() }
+    }
+
+    /// The high scores of `board`, best first.
+    pub fn table(&self, board: &Board) -> &[HighScore] {
+        self.tables.iter()
+            .find(|(b, _)| b == board)
+            .map_or(&[], |(_, entries)| entries.as_slice())
+    }
+
+    /// The position `score` would take in the table of `board`, starting from 0, or `None` if it
+    /// is not good enough to get in. Games without a single point never make it.
+    pub fn rank(&self, board: &Board, score: u32) -> Option<usize> {
+        if score == 0 {
+            return None;
+        }
+
+        // Among equal scores, the oldest stays ahead.
+        let rank = self.table(board).iter().take_while(|e| e.score >= score).count();
+        if rank < MAX_ENTRIES { Some(rank) } else { None }
+    }
+
+    /// Adds `entry` to the table of `board`, if it is good enough, and returns its position.
+    pub fn insert(&mut self, board: &Board, entry: HighScore) -> Option<usize> {
+        let rank = self.rank(board, entry.score)?;
+
+        let index = match self.tables.iter().position(|(b, _)| b == board) {
+            Some(index) => index,
+            None => {
+                self.tables.push((board.clone(), Vec::new()));
+                self.tables.len() - 1
+            },
+        };
+
+        let entries = &mut self.tables[index].1;
+        entries.insert(rank, entry);
+        entries.truncate(MAX_ENTRIES);
+
+        Some(rank)
+    }
+
+    /// Serializes the `HighScores` in the text format described at the top of this module.
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{} {}\n", MAGIC, FORMAT_VERSION);
+
+        for (board, entries) in &self.tables {
+            text.push_str(&format!("table {} {} {} {}\n",
+                                   board.mode.name(), board.hcells, board.vcells, board.level));
+
+            for e in entries {
+                text.push_str(&format!("entry {} {} {} {} {} {}\n",
+                                       e.score, e.length, e.duration.as_millis(), e.seed, e.date, e.name));
+            }
+        }
+
+        text
+    }
+
+    /// Parses `HighScores` in the text format described at the top of this module.
+    pub fn parse(text: &str) -> Result<HighScores, String> {
+        let mut lines = text.lines().enumerate().map(|(i, l)| (i + 1, l));
+
+        match lines.next().map(|(_, l)| l.split_whitespace().collect::<Vec<_>>()) {
+            Some(header) if header.len() == 2 && header[0] == MAGIC => {
+                if header[1] != FORMAT_VERSION.to_string() {
+                    return Err(format!("unsupported high-score format version {}", header[1]));
+                }
+            },
+            _ => return Err(String::from("not a high-score file")),
+        }
+
+        let mut scores = HighScores::new();
+
+        for (line, raw) in lines {
+            let number = |value: &str| value.parse::<u64>()
+                .map_err(|_| format!("line {}: invalid number '{}'", line, value));
+
+            // The last field, a name, may have spaces of its own.
+            let fields = raw.splitn(7, ' ').collect::<Vec<_>>();
+            match fields.as_slice() {
+                [""] => {},
+                ["table", mode, h, v, level @ ..] if !level.is_empty() => {
+                    let board = Board {
+                        mode    : BoardMode::from_name(mode)
+                            .ok_or_else(|| format!("line {}: invalid mode '{}'", line, mode))?,
+                        hcells  : number(h)? as u32,
+                        vcells  : number(v)? as u32,
+                        level   : level.join(" "),
+                    };
+                    scores.tables.push((board, Vec::new()));
+                },
+                ["entry", score, length, millis, seed, date, name] => {
+                    let entry = HighScore {
+                        name    : String::from(*name),
+                        score   : number(score)? as u32,
+                        length  : number(length)? as u32,
+                        duration: Duration::from_millis(number(millis)?),
+                        seed    : number(seed)?,
+                        date    : number(date)?,
+                    };
+
+                    let (_, entries) = scores.tables.last_mut()
+                        .ok_or_else(|| format!("line {}: entry outside of a table", line))?;
+
+                    if entries.last().is_some_and(|last| last.score < entry.score) {
+                        return Err(format!("line {}: entries are not in order", line));
+                    }
+
+                    entries.push(entry);
+                    entries.truncate(MAX_ENTRIES);
+                },
+                _ => return Err(format!("line {}: unexpected '{}'", line, raw)),
+            }
+        }
+
+        Ok(scores)
+    }
+
+    pub fn load(path: &Path) -> Result<HighScores, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("could not read {}: {}", path.display(), e))?;
+
+        HighScores::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        fs::write(path, self.to_text())
+    }
+}
@@ -0,0 +1,571 @@
+//! The rules of the game, free of any SDL structure. A `Simulation` can be advanced one tick at
+//! a time with `step`, which makes it usable from bots, tests and replay tools that have no
+//! display at all. The SDL front-end in `gui.rs` merely renders whatever the `Simulation` holds.
+//!
+//! A bot plays by looking at the `View` of the area on each tick, and then `act`ing:
+//!
+//! ```no_run
+//! use snake_rust::sim::{BoardMode, Cell, Direction, Simulation};
+//!
+//! let mut sim = Simulation::new(20, 15, BoardMode::WALLS, 42).unwrap();
+//! while !sim.over() {
+//!     let view = sim.view();
+//!     let head = view.snake().head();
+//!     let direction = match view.next_cell(head, Direction::UP).map(|c| view.cell(c)) {
+//!         Some(Cell::EMPTY | Cell::FOOD) => Direction::UP,
+//!         _ => Direction::RIGHT,
+//!     };
+//!     sim.act(direction);
+//! }
+//! println!("Scored {}", sim.score());
+//! ```
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::level::Level;
+
+pub mod food;
+use food::FoodSpawner;
+
+pub mod versus;
+
+
+/// A `Snake` can move in any of these directions. Well, that actually depends on the current
+/// direction. E.g. if the `Snake` is moving `LEFT`, it cannot change its direction to `RIGHT`.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Direction {
+    LEFT,
+    RIGHT,
+    UP,
+    DOWN,
+}
+
+
+impl Direction {
+
+    /// The direction a `Snake` can never turn to while moving in `self` direction.
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::LEFT  => Direction::RIGHT,
+            Direction::RIGHT => Direction::LEFT,
+            Direction::UP    => Direction::DOWN,
+            Direction::DOWN  => Direction::UP,
+        }
+    }
+
+    /// The name of the direction, as written in replays and level files.
+    pub fn name(self) -> &'static str {
+        match self {
+            Direction::LEFT  => "LEFT",
+            Direction::RIGHT => "RIGHT",
+            Direction::UP    => "UP",
+            Direction::DOWN  => "DOWN",
+        }
+    }
+
+    /// The direction called `name`, in any case.
+    pub fn from_name(name: &str) -> Option<Direction> {
+        match name.to_uppercase().as_str() {
+            "LEFT"  => Some(Direction::LEFT),
+            "RIGHT" => Some(Direction::RIGHT),
+            "UP"    => Some(Direction::UP),
+            "DOWN"  => Some(Direction::DOWN),
+            _       => None,
+        }
+    }
+}
+
+
+/// The coordinates of a cell in the game area.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Coordinate {
+    pub x       : u32,
+    pub y       : u32
+}
+
+
+/// A `Snake` is essentially a vector of cells in the grid, whose head is moving in certain
+/// `direction`.
+#[derive(Debug, Clone)]
+pub struct Snake {
+    pub direction   :   Direction,
+    pub body        :   Vec<Coordinate>,
+}
+
+
+impl Snake {
+
+    /// Coordinate of the cell the head of the `Snake` is currently in.
+    pub fn head(&self) -> Coordinate {
+        self.body[0]
+    }
+}
+
+
+/// The most cells an area may have. Every cell is tracked, so larger ones would take too much
+/// memory to play on.
+pub const MAX_CELLS: usize = 1 << 20;
+
+
+/// Number of cells of an area of `hcells` x `vcells` cells. Fails if there are more than
+/// `MAX_CELLS`.
+pub fn area(hcells: u32, vcells: u32) -> Result<usize, String> {
+    (hcells as usize).checked_mul(vcells as usize)
+        .filter(|cells| *cells <= MAX_CELLS)
+        .ok_or_else(|| format!("a {}x{} area has more than the {} cells allowed", hcells, vcells, MAX_CELLS))
+}
+
+
+/// Create a `Snake` with a certain number of cells as its body, centered in an area of
+/// `hcells` x `vcells` cells without internal walls. It always heads `RIGHT`.
+pub fn create_snake(hcells: u32, vcells: u32) -> Snake {
+    spawn_snake(&Level::open(hcells, vcells))
+}
+
+
+/// Create a `Snake` at the spawn point of `level`.
+pub fn spawn_snake(level: &Level) -> Snake {
+    Snake {
+        direction: level.direction,
+        body     : level.snake_body(),
+    }
+}
+
+
+/// The cell next to `from` on an area of `hcells` x `vcells` cells, in the given `direction`, or
+/// `None` if that means running into the edge.
+fn next_cell(hcells: u32, vcells: u32, mode: BoardMode, from: Coordinate, direction: Direction) -> Option<Coordinate> {
+    let wrap = mode == BoardMode::WRAP;
+    let mut cell = from;
+
+    match direction {
+        Direction::LEFT => {
+            if cell.x == 0 {
+                if !wrap {
+                    return None;
+                }
+                cell.x = hcells;
+            }
+
+            cell.x -= 1;
+        },
+        Direction::RIGHT => {
+            cell.x += 1;
+
+            if cell.x == hcells {
+                if !wrap {
+                    return None;
+                }
+                cell.x = 0;
+            }
+        },
+        Direction::UP => {
+            if cell.y == 0 {
+                if !wrap {
+                    return None;
+                }
+                cell.y = vcells;
+            }
+
+            cell.y -= 1;
+        },
+        Direction::DOWN => {
+            cell.y += 1;
+
+            if cell.y == vcells {
+                if !wrap {
+                    return None;
+                }
+                cell.y = 0;
+            }
+        },
+    }
+
+    Some(cell)
+}
+
+
+/// Picks a free cell of `level` for the food. If the `Level` has food spots, one of them is used
+/// as long as there is one free; otherwise any free cell will do.
+fn spawn_food(level: &Level, spawner: &FoodSpawner, rng: &mut ChaCha8Rng) -> Option<Coordinate> {
+    let spots: Vec<Coordinate> = level.food_spots.iter()
+        .copied()
+        .filter(|spot| spawner.is_free(*spot))
+        .collect();
+
+    if !spots.is_empty() {
+        return Some(spots[rng.gen_range(0..spots.len())]);
+    }
+
+    spawner.spawn(rng)
+}
+
+
+/// What happens when the `Snake` reaches the edge of the area.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum BoardMode {
+    /// The edges are walls: running into them ends the game.
+    WALLS,
+    /// The edges are open: leaving the area on one side enters it again on the opposite side.
+    WRAP,
+}
+
+
+impl BoardMode {
+
+    /// The name of the mode, as written in replays and high score files.
+    pub fn name(self) -> &'static str {
+        match self {
+            BoardMode::WALLS => "WALLS",
+            BoardMode::WRAP  => "WRAP",
+        }
+    }
+
+    /// The mode called `name`.
+    pub fn from_name(name: &str) -> Option<BoardMode> {
+        match name {
+            "WALLS" => Some(BoardMode::WALLS),
+            "WRAP"  => Some(BoardMode::WRAP),
+            _       => None,
+        }
+    }
+}
+
+
+/// What a cell of the area holds, as seen in a `View`.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Cell {
+    EMPTY,
+    WALL,
+    FOOD,
+    /// The head of the `Snake` at this index in `View::snakes`.
+    HEAD(usize),
+    /// Any other cell of the body of the `Snake` at this index in `View::snakes`.
+    BODY(usize),
+}
+
+
+/// What a `Snake` sees of the area on a tick: enough for a bot or the AI to decide where to go.
+#[derive(Debug, Clone)]
+pub struct View<'a> {
+    pub hcells      : u32,
+    pub vcells      : u32,
+    pub mode        : BoardMode,
+    pub walls       : &'a [Coordinate],
+    /// Every `Snake` on the area, including those that died in a versus game, which stay there
+    /// as obstacles.
+    pub snakes      : Vec<&'a Snake>,
+    /// Index in `snakes` of the `Snake` deciding.
+    pub me          : usize,
+    pub food        : Option<Coordinate>,
+    /// Number of times the `Snake` deciding ate.
+    pub score       : u32,
+}
+
+
+impl<'a> View<'a> {
+
+    /// The `Snake` deciding.
+    pub fn snake(&self) -> &'a Snake {
+        self.snakes[self.me]
+    }
+
+    /// The cell next to `from` in the given `direction`, or `None` if that means running into
+    /// the edge.
+    pub fn next_cell(&self, from: Coordinate, direction: Direction) -> Option<Coordinate> {
+        next_cell(self.hcells, self.vcells, self.mode, from, direction)
+    }
+
+    /// What the cell at `c` holds.
+    pub fn cell(&self, c: Coordinate) -> Cell {
+        if self.food == Some(c) {
+            return Cell::FOOD;
+        }
+
+        if self.walls.contains(&c) {
+            return Cell::WALL;
+        }
+
+        for (i, snake) in self.snakes.iter().enumerate() {
+            if let Some(k) = snake.body.iter().position(|b| *b == c) {
+                return if k == 0 { Cell::HEAD(i) } else { Cell::BODY(i) };
+            }
+        }
+
+        Cell::EMPTY
+    }
+
+    /// What every cell of the area holds, indexed as `y * hcells + x`.
+    pub fn cells(&self) -> Vec<Cell> {
+        let mut cells = vec![Cell::EMPTY; self.hcells as usize * self.vcells as usize];
+        let index = |c: &Coordinate| c.y as usize * self.hcells as usize + c.x as usize;
+
+        for w in self.walls {
+            cells[index(w)] = Cell::WALL;
+        }
+
+        for (i, snake) in self.snakes.iter().enumerate() {
+            for (k, c) in snake.body.iter().enumerate() {
+                cells[index(c)] = if k == 0 { Cell::HEAD(i) } else { Cell::BODY(i) };
+            }
+        }
+
+        if let Some(food) = &self.food {
+            cells[index(food)] = Cell::FOOD;
+        }
+
+        cells
+    }
+}
+
+
+/// What happened during a single `Simulation::step`.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum StepOutcome {
+    /// The `Snake` moved one cell.
+    MOVED,
+    /// The `Snake` moved onto the food and grew one cell.
+    ATE,
+    /// The `Snake` hit a wall (the edge of the area or one inside it) or itself. The game is
+    /// over.
+    COLLIDED,
+    /// The `Snake` ate and now fills the whole area, so there is nowhere left to put the food.
+    /// The game is won.
+    WON,
+}
+
+
+/// What the `Snake` ran into, ending the game.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Collision {
+    /// The edge of the area, when it is not open.
+    EDGE,
+    /// A wall inside the area.
+    WALL,
+    /// Its own body.
+    BODY,
+    /// The body of another `Snake`, in a versus game.
+    SNAKE,
+    /// The head of another `Snake`, both moving into the same cell or through each other.
+    HEAD,
+}
+
+
+/// The state of a game: the `Snake`, the food and the score, on an area laid out by a `Level`.
+/// All randomness comes from an RNG initialized with `seed`, so the same seed and the same inputs
+/// always produce the same game.
+pub struct Simulation {
+    level       : Level,
+    mode        : BoardMode,
+    snake       : Snake,
+    /// Where the food is, or `None` once the `Snake` covers every cell.
+    food        : Option<Coordinate>,
+    score       : u32,
+    /// Cells not covered by the `Snake` or the walls, where food can be placed.
+    spawner     : FoodSpawner,
+    seed        : u64,
+    rng         : ChaCha8Rng,
+    /// Number of times the game has been `step`ped since it started.
+    ticks       : u64,
+    /// Number of times the `Snake` ate.
+    eaten       : u32,
+    /// What the `Snake` collided with, once it did.
+    collision   : Option<Collision>,
+}
+
+
+impl Simulation {
+
+    /// Create a new `Simulation` on an area of `hcells` x `vcells` cells, whose edges behave as
+    /// `mode` says, with the `Snake` in the middle and the food at a random location picked by an
+    /// RNG initialized with `seed`. Fails if the `Snake` does not fit in the area.
+    pub fn new(hcells: u32, vcells: u32, mode: BoardMode, seed: u64) -> Result<Simulation, String> {
+        Simulation::from_level(Level::open(hcells, vcells), mode, seed)
+    }
+
+    /// Create a new `Simulation` on the area laid out by `level`, whose edges behave as `mode`
+    /// says. The food is placed by an RNG initialized with `seed`. Fails if the `Snake` does not
+    /// fit in the level.
+    pub fn from_level(level: Level, mode: BoardMode, seed: u64) -> Result<Simulation, String> {
+        level.validate()?;
+
+        let mut sim = Simulation {
+            mode,
+            snake   : spawn_snake(&level),
+            food    : None,
+            score   : 0,
+            spawner : FoodSpawner::new(level.hcells, level.vcells),
+            level,
+            seed,
+            rng     : ChaCha8Rng::seed_from_u64(seed),
+            ticks   : 0,
+            eaten   : 0,
+            collision: None,
+        };
+
+        sim.reset(seed);
+        Ok(sim)
+    }
+
+    /// Start the game over, on the same area: a new `Snake`, new food and a score of zero. The
+    /// RNG is re-initialized with `seed`.
+    pub fn reset(&mut self, seed: u64) {
+        self.seed  = seed;
+        self.rng   = ChaCha8Rng::seed_from_u64(seed);
+
+        self.snake   = spawn_snake(&self.level);
+        self.spawner = FoodSpawner::new(self.level.hcells, self.level.vcells);
+        for c in self.level.walls.iter().chain(&self.snake.body) {
+            self.spawner.occupy(*c);
+        }
+
+        self.food  = self.spawn_food();
+        self.score = 0;
+        self.ticks = 0;
+        self.eaten = 0;
+        self.collision = None;
+    }
+
+    pub fn hcells(&self) -> u32 {
+        self.level.hcells
+    }
+
+    pub fn vcells(&self) -> u32 {
+        self.level.vcells
+    }
+
+    pub fn level(&self) -> &Level {
+        &self.level
+    }
+
+    pub fn walls(&self) -> &[Coordinate] {
+        &self.level.walls
+    }
+
+    pub fn mode(&self) -> BoardMode {
+        self.mode
+    }
+
+    pub fn snake(&self) -> &Snake {
+        &self.snake
+    }
+
+    pub fn food(&self) -> Option<Coordinate> {
+        self.food
+    }
+
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    /// Number of ticks played so far. This is also the number of the tick the next `step` plays.
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Number of times the `Snake` ate since the game started.
+    pub fn food_eaten(&self) -> u32 {
+        self.eaten
+    }
+
+    /// What ended the game, if the `Snake` collided.
+    pub fn collision(&self) -> Option<Collision> {
+        self.collision
+    }
+
+    /// The seed this game was started with.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// What the `Snake` sees of the area.
+    pub fn view(&self) -> View<'_> {
+        View {
+            hcells  : self.level.hcells,
+            vcells  : self.level.vcells,
+            mode    : self.mode,
+            walls   : &self.level.walls,
+            snakes  : vec![&self.snake],
+            me      : 0,
+            food    : self.food,
+            score   : self.score,
+        }
+    }
+
+    /// Whether the game is over: the `Snake` collided, or it fills the whole area.
+    pub fn over(&self) -> bool {
+        self.collision.is_some() || self.food.is_none()
+    }
+
+    /// Picks a free cell for the food, see `spawn_food`.
+    fn spawn_food(&mut self) -> Option<Coordinate> {
+        spawn_food(&self.level, &self.spawner, &mut self.rng)
+    }
+
+    /// The cell next to `from` in the given `direction`, or `None` if that means running into
+    /// a wall.
+    pub fn next_cell(&self, from: Coordinate, direction: Direction) -> Option<Coordinate> {
+        next_cell(self.level.hcells, self.level.vcells, self.mode, from, direction)
+    }
+
+    /// Advance the game by one tick, turning the `Snake` to `direction` first unless it is the
+    /// opposite of its current one. The same as `step`, for bots that always say where to go.
+    pub fn act(&mut self, direction: Direction) -> StepOutcome {
+        self.step(Some(direction))
+    }
+
+    /// Advance the game by one tick. If `input` is given, the `Snake` turns to that direction
+    /// before moving, unless it is the opposite of its current direction.
+    pub fn step(&mut self, input: Option<Direction>) -> StepOutcome {
+        self.ticks += 1;
+
+        if let Some(direction) = input {
+            if direction != self.snake.direction.opposite() {
+                self.snake.direction = direction;
+            }
+        }
+
+        let new_head = match self.next_cell(self.snake.head(), self.snake.direction) {
+            Some(cell) => cell,
+            None => {
+                self.collision = Some(Collision::EDGE);
+                return StepOutcome::COLLIDED;
+            },
+        };
+
+        let ate = self.food == Some(new_head);
+        let tail = *self.snake.body.last().unwrap();
+
+        // The tail moves out of the way before the head moves in, so the head may take the cell
+        // the tail is leaving. The `Snake` is left as it was when it collides.
+        if !self.spawner.is_free(new_head) && (ate || new_head != tail) {
+            self.collision = match self.level.walls.contains(&new_head) {
+                true  => Some(Collision::WALL),
+                false => Some(Collision::BODY),
+            };
+            return StepOutcome::COLLIDED;
+        }
+
+        if !ate {
+            self.snake.body.pop();
+            self.spawner.release(tail);
+        }
+
+        self.snake.body.insert(0, new_head);
+        self.spawner.occupy(new_head);
+
+        if !ate {
+            return StepOutcome::MOVED;
+        }
+
+        self.score += 1;
+        self.eaten += 1;
+        self.food = self.spawn_food();
+
+        match self.food {
+            Some(_) => StepOutcome::ATE,
+            None    => StepOutcome::WON,
+        }
+    }
+}
@@ -0,0 +1,85 @@
+//! Placement of the food. We keep track of every cell not covered by the `Snake`, so a new
+//! location for the food can be sampled uniformly in constant time, no matter how much of the
+//! area the `Snake` already fills.
+
+use rand::Rng;
+
+use super::Coordinate;
+
+
+/// The set of free cells in an area of `hcells` x `vcells` cells.
+pub struct FoodSpawner {
+    hcells      : u32,
+    /// Every free cell, in no particular order.
+    free        : Vec<Coordinate>,
+    /// For each cell (indexed as `y * hcells + x`), its position inside `free`, or `None` if the
+    /// cell is occupied.
+    slots       : Vec<Option<usize>>,
+}
+
+
+impl FoodSpawner {
+
+    /// Create a `FoodSpawner` for an area of `hcells` x `vcells` cells, all of them free.
+    pub fn new(hcells: u32, vcells: u32) -> FoodSpawner {
+        let mut free = Vec::with_capacity(hcells as usize * vcells as usize);
+
+        for y in 0..vcells {
+            for x in 0..hcells {
+                free.push(Coordinate { x, y });
+            }
+        }
+
+        FoodSpawner {
+            hcells,
+            slots: (0..free.len()).map(Some).collect(),
+            free,
+        }
+    }
+
+    fn index(&self, coord: Coordinate) -> usize {
+        coord.y as usize * self.hcells as usize + coord.x as usize
+    }
+
+    /// Every free cell, in no particular order.
+    pub fn free_cells(&self) -> &[Coordinate] {
+        &self.free
+    }
+
+    pub fn is_free(&self, coord: Coordinate) -> bool {
+        self.slots[self.index(coord)].is_some()
+    }
+
+    /// Mark `coord` as occupied. Occupying a cell twice is a no-op.
+    pub fn occupy(&mut self, coord: Coordinate) {
+        let index = self.index(coord);
+
+        if let Some(slot) = self.slots[index].take() {
+            // Fill the hole with the last free cell, which keeps `free` compact.
+            self.free.swap_remove(slot);
+            if let Some(moved) = self.free.get(slot) {
+                let moved_index = self.index(*moved);
+                self.slots[moved_index] = Some(slot);
+            }
+        }
+    }
+
+    /// Mark `coord` as free again. Releasing a free cell is a no-op.
+    pub fn release(&mut self, coord: Coordinate) {
+        let index = self.index(coord);
+
+        if self.slots[index].is_none() {
+            self.slots[index] = Some(self.free.len());
+            self.free.push(coord);
+        }
+    }
+
+    /// Pick a free cell at random. `None` means there is no free cell left.
+    pub fn spawn<R: Rng>(&self, rng: &mut R) -> Option<Coordinate> {
+        if self.free.is_empty() {
+            return None;
+        }
+
+        Some(self.free[rng.gen_range(0..self.free.len())])
+    }
+}
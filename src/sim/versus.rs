@@ -0,0 +1,446 @@
+//! Versus games: several snakes on the same area, racing for the same food. A round lasts until
+//! at most one `Snake` is left; the last one standing wins it. Every tick is resolved at once for
+//! all the snakes, so the outcome never depends on the order of the players:
+//!
+//! - The tails of the snakes that are not about to eat move out of the way first.
+//! - Then every head is checked against the board as it is at that point. Heads moving into the
+//!   same cell, or through each other, collide head-to-head and all of them die. A head moving
+//!   into a body, its own or another one, dies.
+//! - The snakes that died stay on the area as obstacles until the round ends.
+//!
+//! A `Match` keeps the count of the rounds won by each player.
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::level::Level;
+
+use super::food::FoodSpawner;
+use super::{area, next_cell, spawn_food, BoardMode, Collision, Coordinate, Direction, Snake, View};
+
+
+/// How many snakes can share the area.
+pub const MAX_PLAYERS: usize = 4;
+/// Rounds a player must win to take the match.
+pub const ROUNDS_TO_WIN: u32 = 3;
+
+
+/// How a round stands after a `Versus::step`.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum RoundOutcome {
+    /// More than one `Snake` is still moving.
+    PLAYING,
+    /// The player at this index won the round: every other `Snake` died, or the area is full
+    /// and it has the best score.
+    WON(usize),
+    /// The last snakes died on the same tick, or the area is full and the best score is tied.
+    DRAW,
+}
+
+
+/// One of the snakes of a `Versus` game.
+#[derive(Debug, Clone)]
+pub struct Player {
+    snake       : Snake,
+    /// Number of times the `Snake` ate this round.
+    score       : u32,
+    /// What the `Snake` collided with, once it did.
+    collision   : Option<Collision>,
+}
+
+
+impl Player {
+
+    pub fn snake(&self) -> &Snake {
+        &self.snake
+    }
+
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    pub fn collision(&self) -> Option<Collision> {
+        self.collision
+    }
+
+    pub fn alive(&self) -> bool {
+        self.collision.is_none()
+    }
+}
+
+
+/// Where the snakes start a round on `level`: each on its own row, spread over the area, every
+/// other one heading the opposite way so nobody starts right in front of someone else.
+fn spawn_snakes(level: &Level, players: usize) -> Result<Vec<Snake>, String> {
+    let length = level.length.max(1);
+    let margin = (level.hcells / 4).max(length - 1);
+    let no_room = || format!("{} has no room for {} snakes", level.name, players);
+    if margin >= level.hcells {
+        return Err(no_room());
+    }
+
+    let mut snakes: Vec<Snake> = Vec::with_capacity(players);
+
+    for i in 0..players {
+        let y = (i as u32 + 1) * level.vcells / (players as u32 + 1);
+        let (direction, body) = match i % 2 {
+            0 => (Direction::RIGHT, (0..length).map(|k| Coordinate { x: margin - k, y }).collect()),
+            _ => (Direction::LEFT, (0..length).map(|k| Coordinate { x: level.hcells - 1 - margin + k, y }).collect()),
+        };
+
+        let snake = Snake { direction, body };
+        let fits = snake.body.iter().all(|c| {
+            c.x < level.hcells && c.y < level.vcells && !level.walls.contains(c) &&
+            !snakes.iter().any(|s| s.body.contains(c))
+        });
+
+        if !fits {
+            return Err(no_room());
+        }
+
+        snakes.push(snake);
+    }
+
+    Ok(snakes)
+}
+
+
+/// The state of a round of a versus game: every `Player`, the food, on an area laid out by a
+/// `Level`. As with a `Simulation`, all randomness comes from an RNG initialized with `seed`.
+pub struct Versus {
+    level       : Level,
+    mode        : BoardMode,
+    players     : Vec<Player>,
+    /// Where the food is, or `None` once the snakes cover every cell.
+    food        : Option<Coordinate>,
+    /// Cells not covered by any `Snake` or the walls, where food can be placed.
+    spawner     : FoodSpawner,
+    seed        : u64,
+    rng         : ChaCha8Rng,
+    /// Number of times the round has been `step`ped since it started.
+    ticks       : u64,
+    outcome     : RoundOutcome,
+}
+
+
+impl Versus {
+
+    /// Create a round for `players` snakes on the area laid out by `level`, whose edges behave as
+    /// `mode` says. Fails if there are not between 2 and `MAX_PLAYERS` players, if they don't fit,
+    /// or if the area is too large.
+    pub fn new(level: Level, mode: BoardMode, players: usize, seed: u64) -> Result<Versus, String> {
+        if !(2..=MAX_PLAYERS).contains(&players) {
+            return Err(format!("a versus game needs between 2 and {} players, not {}", MAX_PLAYERS, players));
+        }
+
+        area(level.hcells, level.vcells)?;
+
+        let snakes = spawn_snakes(&level, players)?;
+
+        let mut versus = Versus {
+            mode,
+            players : snakes.into_iter().map(|snake| Player { snake, score: 0, collision: None }).collect(),
+            food    : None,
+            spawner : FoodSpawner::new(level.hcells, level.vcells),
+            level,
+            seed,
+            rng     : ChaCha8Rng::seed_from_u64(seed),
+            ticks   : 0,
+            outcome : RoundOutcome::PLAYING,
+        };
+
+        versus.reset(seed);
+        Ok(versus)
+    }
+
+    /// Start a new round, with the same players on the same area. The RNG is re-initialized with
+    /// `seed`.
+    pub fn reset(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng  = ChaCha8Rng::seed_from_u64(seed);
+
+        // The number of players was checked to fit when the `Versus` was created.
+        let snakes = spawn_snakes(&self.level, self.players.len()).unwrap();
+
+        self.spawner = FoodSpawner::new(self.level.hcells, self.level.vcells);
+        for c in self.level.walls.iter().chain(snakes.iter().flat_map(|s| &s.body)) {
+            self.spawner.occupy(*c);
+        }
+
+        self.players = snakes.into_iter()
+            .map(|snake| Player { snake, score: 0, collision: None })
+            .collect();
+
+        self.food    = spawn_food(&self.level, &self.spawner, &mut self.rng);
+        self.ticks   = 0;
+        self.outcome = RoundOutcome::PLAYING;
+    }
+
+    pub fn hcells(&self) -> u32 {
+        self.level.hcells
+    }
+
+    pub fn vcells(&self) -> u32 {
+        self.level.vcells
+    }
+
+    pub fn level(&self) -> &Level {
+        &self.level
+    }
+
+    pub fn walls(&self) -> &[Coordinate] {
+        &self.level.walls
+    }
+
+    pub fn mode(&self) -> BoardMode {
+        self.mode
+    }
+
+    pub fn players(&self) -> &[Player] {
+        &self.players
+    }
+
+    pub fn food(&self) -> Option<Coordinate> {
+        self.food
+    }
+
+    /// Number of ticks played so far this round.
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// The seed this round was started with.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn outcome(&self) -> RoundOutcome {
+        self.outcome
+    }
+
+    /// What the `Snake` of `player` sees of the area.
+    pub fn view(&self, player: usize) -> View<'_> {
+        View {
+            hcells  : self.level.hcells,
+            vcells  : self.level.vcells,
+            mode    : self.mode,
+            walls   : &self.level.walls,
+            snakes  : self.players.iter().map(|p| &p.snake).collect(),
+            me      : player,
+            food    : self.food,
+            score   : self.players[player].score,
+        }
+    }
+
+    /// Advance the round by one tick. Each `Snake` still moving turns to the direction at its
+    /// index in `inputs`, if any, unless it is the opposite of its current direction. Returns
+    /// how the round stands afterwards; once it is over, stepping does nothing.
+    pub fn step(&mut self, inputs: &[Option<Direction>]) -> RoundOutcome {
+        if self.outcome != RoundOutcome::PLAYING {
+            return self.outcome;
+        }
+
+        self.ticks += 1;
+
+        let old_heads: Vec<Coordinate> = self.players.iter().map(|p| p.snake.head()).collect();
+        let mut heads: Vec<Option<Coordinate>> = vec![None; self.players.len()];
+
+        for (i, player) in self.players.iter_mut().enumerate() {
+            if !player.alive() {
+                continue;
+            }
+
+            if let Some(Some(direction)) = inputs.get(i) {
+                if *direction != player.snake.direction.opposite() {
+                    player.snake.direction = *direction;
+                }
+            }
+
+            heads[i] = next_cell(self.level.hcells, self.level.vcells, self.mode, player.snake.head(), player.snake.direction);
+            if heads[i].is_none() {
+                player.collision = Some(Collision::EDGE);
+            }
+        }
+
+        let eats: Vec<bool> = heads.iter().map(|h| h.is_some() && *h == self.food).collect();
+
+        // The tails move out of the way before any head moves in, so a head may take the cell a
+        // tail just left, whoever it belonged to.
+        let mut tails: Vec<Option<Coordinate>> = vec![None; self.players.len()];
+        for (i, player) in self.players.iter_mut().enumerate() {
+            if heads[i].is_some() && !eats[i] {
+                let tail = player.snake.body.pop().unwrap();
+                self.spawner.release(tail);
+                tails[i] = Some(tail);
+            }
+        }
+
+        // A `Snake` that collides is left as it was, so its tail comes back, and may be in the
+        // way of another head in turn.
+        let mut collisions: Vec<Option<Collision>> = vec![None; self.players.len()];
+        loop {
+            let mut settled = true;
+
+            for i in 0..self.players.len() {
+                let head = match heads[i] {
+                    Some(head) if collisions[i].is_none() => head,
+                    _ => continue,
+                };
+
+                collisions[i] = self.collision(i, head, &heads, &old_heads);
+                if collisions[i].is_some() {
+                    settled = false;
+
+                    if let Some(tail) = tails[i].take() {
+                        self.players[i].snake.body.push(tail);
+                        self.spawner.occupy(tail);
+                    }
+                }
+            }
+
+            if settled {
+                break;
+            }
+        }
+
+        let mut ate = false;
+        for (i, player) in self.players.iter_mut().enumerate() {
+            let head = match heads[i] {
+                Some(head) => head,
+                None => continue,
+            };
+
+            if collisions[i].is_some() {
+                player.collision = collisions[i];
+                continue;
+            }
+
+            player.snake.body.insert(0, head);
+            self.spawner.occupy(head);
+
+            if eats[i] {
+                player.score += 1;
+                ate = true;
+            }
+        }
+
+        if ate {
+            self.food = spawn_food(&self.level, &self.spawner, &mut self.rng);
+        }
+
+        self.outcome = self.judge();
+        self.outcome
+    }
+
+    /// What the `Snake` of `player` runs into when its head moves to `head`, given where every
+    /// head is moving to from where.
+    fn collision(&self, player: usize, head: Coordinate, heads: &[Option<Coordinate>], old_heads: &[Coordinate]) -> Option<Collision> {
+        let head_on = (0..self.players.len()).any(|j| {
+            j != player && (heads[j] == Some(head) ||
+                            (heads[j] == Some(old_heads[player]) && head == old_heads[j]))
+        });
+
+        if head_on {
+            Some(Collision::HEAD)
+        }
+        else if self.spawner.is_free(head) {
+            None
+        }
+        else if self.level.walls.contains(&head) {
+            Some(Collision::WALL)
+        }
+        else if self.players[player].snake.body.contains(&head) {
+            Some(Collision::BODY)
+        }
+        else {
+            Some(Collision::SNAKE)
+        }
+    }
+
+    /// How the round stands, from the snakes still moving.
+    fn judge(&self) -> RoundOutcome {
+        let alive: Vec<usize> = (0..self.players.len()).filter(|i| self.players[*i].alive()).collect();
+
+        match alive.len() {
+            0 => RoundOutcome::DRAW,
+            1 => RoundOutcome::WON(alive[0]),
+            _ if self.food.is_none() => {
+                let best = alive.iter().map(|i| self.players[*i].score).max().unwrap();
+                let leaders: Vec<usize> = alive.into_iter().filter(|i| self.players[*i].score == best).collect();
+
+                match leaders[..] {
+                    [winner] => RoundOutcome::WON(winner),
+                    _ => RoundOutcome::DRAW,
+                }
+            },
+            _ => RoundOutcome::PLAYING,
+        }
+    }
+}
+
+
+/// The rounds played between the same players, until one of them wins `ROUNDS_TO_WIN`.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Match {
+    /// Rounds won by every player.
+    wins        : Vec<u32>,
+    /// Points scored by every player, over all rounds.
+    points      : Vec<u32>,
+    rounds      : u32,
+    draws       : u32,
+}
+
+
+impl Match {
+
+    pub fn new(players: usize) -> Match {
+        Match {
+            wins    : vec![0; players],
+            points  : vec![0; players],
+            rounds  : 0,
+            draws   : 0,
+        }
+    }
+
+    pub fn players(&self) -> usize {
+        self.wins.len()
+    }
+
+    /// Records the round `versus` just finished: who won it, and the points every player scored.
+    /// Rounds still being played are not recorded.
+    pub fn record(&mut self, versus: &Versus) {
+        match versus.outcome() {
+            RoundOutcome::PLAYING => return,
+            RoundOutcome::WON(winner) => self.wins[winner] += 1,
+            RoundOutcome::DRAW => self.draws += 1,
+        }
+
+        for (points, player) in self.points.iter_mut().zip(versus.players()) {
+            *points += player.score();
+        }
+
+        self.rounds += 1;
+    }
+
+    /// Number of rounds recorded so far.
+    pub fn rounds(&self) -> u32 {
+        self.rounds
+    }
+
+    pub fn draws(&self) -> u32 {
+        self.draws
+    }
+
+    pub fn wins(&self, player: usize) -> u32 {
+        self.wins[player]
+    }
+
+    pub fn points(&self, player: usize) -> u32 {
+        self.points[player]
+    }
+
+    /// The player who won the match, once someone won enough rounds.
+    pub fn winner(&self) -> Option<usize> {
+        self.wins.iter().position(|w| *w >= ROUNDS_TO_WIN)
+    }
+}
@@ -0,0 +1,87 @@
+use snake_rust::ai::{play, Strategy};
+use snake_rust::config::Difficulty;
+use snake_rust::level::Level;
+use snake_rust::sim::versus::{RoundOutcome, Versus};
+use snake_rust::sim::{BoardMode, Simulation, StepOutcome};
+
+#[test]
+fn strategies_are_found_by_name() {
+    for strategy in Strategy::ALL {
+        assert_eq!(Strategy::from_name(strategy.name()), Some(strategy));
+    }
+
+    assert_eq!(Strategy::from_name("AStar"), Some(Strategy::ASTAR));
+    assert_eq!(Strategy::from_name("random"), None);
+    assert_eq!(Strategy::for_difficulty(Difficulty::EASY), Strategy::GREEDY);
+}
+
+#[test]
+fn greedy_heads_for_the_food() {
+    for seed in 1..=5 {
+        let mut sim = Simulation::new(20, 15, BoardMode::WALLS, seed).unwrap();
+
+        // Nothing is in the way on an empty area, so the food is reached by the shortest path.
+        let food = sim.food().unwrap();
+        let head = sim.snake().head();
+        let distance = (food.x.abs_diff(head.x) + food.y.abs_diff(head.y)) as u64;
+        let ticks = if food.y == head.y && food.x < head.x { distance + 2 } else { distance };
+
+        let mut greedy = Strategy::GREEDY.controller();
+        play(&mut sim, greedy.as_mut(), ticks);
+        assert_eq!(sim.score(), 1, "seed {}", seed);
+    }
+}
+
+#[test]
+fn astar_eats_a_lot_without_dying() {
+    for seed in 1..=3 {
+        let mut sim = Simulation::new(20, 15, BoardMode::WALLS, seed).unwrap();
+        let mut astar = Strategy::ASTAR.controller();
+
+        while sim.score() < 100 {
+            let input = astar.decide(&sim.view());
+            assert_ne!(sim.step(input), StepOutcome::COLLIDED, "seed {}", seed);
+        }
+    }
+}
+
+#[test]
+fn hamiltonian_fills_the_area() {
+    let mut sim = Simulation::new(8, 6, BoardMode::WALLS, 1).unwrap();
+    let mut hamiltonian = Strategy::HAMILTONIAN.controller();
+
+    assert_eq!(play(&mut sim, hamiltonian.as_mut(), 10_000), Some(StepOutcome::WON));
+    assert_eq!(sim.snake().body.len(), 8 * 6);
+
+    // Also when the cycle has to run along the columns.
+    let mut sim = Simulation::new(8, 7, BoardMode::WRAP, 1).unwrap();
+    let mut hamiltonian = Strategy::HAMILTONIAN.controller();
+    assert_eq!(play(&mut sim, hamiltonian.as_mut(), 10_000), Some(StepOutcome::WON));
+    assert_eq!(sim.snake().body.len(), 8 * 7);
+}
+
+#[test]
+fn computer_snakes_play_a_versus_round() {
+    let play_round = || {
+        let mut versus = Versus::new(Level::open(38, 28), BoardMode::WALLS, 2, 7).unwrap();
+        let mut controllers = [Strategy::GREEDY.controller(), Strategy::ASTAR.controller()];
+
+        while versus.outcome() == RoundOutcome::PLAYING && versus.ticks() < 100_000 {
+            let inputs: Vec<_> = (0..controllers.len())
+                .map(|i| match versus.players()[i].alive() {
+                    true  => controllers[i].decide(&versus.view(i)),
+                    false => None,
+                })
+                .collect();
+
+            versus.step(&inputs);
+        }
+
+        (versus.outcome(), versus.ticks())
+    };
+
+    let (outcome, ticks) = play_round();
+    assert_ne!(outcome, RoundOutcome::PLAYING);
+    // The same seed always plays the same round.
+    assert_eq!(play_round(), (outcome, ticks));
+}
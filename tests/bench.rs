@@ -0,0 +1,83 @@
+use snake_rust::ai::Strategy;
+use snake_rust::bench::{self, BenchConfig, GameResult, Report};
+use snake_rust::sim::Collision;
+
+fn result(seed: u64, score: u32, ticks: u64, collision: Option<Collision>) -> GameResult {
+    GameResult { seed, score, ticks, collision, won: false }
+}
+
+#[test]
+fn reports_sum_up_the_games() {
+    let results = [
+        result(1, 4, 100, Some(Collision::BODY)),
+        result(2, 10, 300, Some(Collision::EDGE)),
+        result(3, 6, 200, Some(Collision::BODY)),
+        GameResult { won: true, ..result(4, 20, 500, None) },
+        result(5, 2, 1000, None),
+    ];
+
+    let report = Report::new(Strategy::GREEDY, &results);
+    assert_eq!(report.games, 5);
+    assert_eq!(report.mean_score, 8.4);
+    assert_eq!(report.median_score, 6.0);
+    assert_eq!(report.max_score, 20);
+    assert_eq!(report.win_rate, 0.2);
+    assert_eq!(report.mean_steps_to_death, 200.0);
+    assert_eq!(report.median_steps_to_death, 200.0);
+    assert_eq!(report.timeouts, 1);
+    assert_eq!(report.deaths, vec![(Collision::EDGE, 1), (Collision::WALL, 0), (Collision::BODY, 2)]);
+
+    // With an even number of games, the median is between the two in the middle.
+    assert_eq!(Report::new(Strategy::GREEDY, &results[..4]).median_score, 8.0);
+}
+
+#[test]
+fn threads_do_not_change_the_results() {
+    let mut config = BenchConfig::new(10, 8);
+    config.games = 12;
+    config.seed = 5;
+    config.max_ticks = 2000;
+
+    config.threads = 1;
+    let alone = bench::run(&config).unwrap();
+    config.threads = 5;
+    let together = bench::run(&config).unwrap();
+
+    assert_eq!(alone, together);
+    assert_eq!(alone.len(), Strategy::ALL.len());
+    assert!(alone.iter().all(|r| r.games == 12));
+
+    let games = bench::play_games(&config, Strategy::ASTAR).unwrap();
+    assert_eq!(games.iter().map(|g| g.seed).collect::<Vec<_>>(), (5..17).collect::<Vec<_>>());
+    assert_eq!(Ok(games[3]), bench::play_game(&config, Strategy::ASTAR, 8));
+
+    // The `Snake` does not fit on a board this small.
+    assert!(bench::run(&BenchConfig::new(4, 4)).is_err());
+}
+
+#[test]
+fn hamiltonian_wins_within_the_default_tick_cap() {
+    let mut config = BenchConfig::new(16, 12);
+    config.strategies = vec![Strategy::HAMILTONIAN];
+    config.games = 2;
+
+    let reports = bench::run(&config).unwrap();
+    assert_eq!(reports[0].win_rate, 1.0);
+    assert_eq!(reports[0].timeouts, 0);
+}
+
+#[test]
+fn reports_are_written_as_csv_and_json() {
+    let reports = vec![Report::new(Strategy::ASTAR, &[result(1, 4, 100, Some(Collision::WALL))])];
+
+    let csv = bench::to_csv(&reports);
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("strategy,games,mean_score"));
+    assert_eq!(lines[1], "astar,1,4.00,4.0,4,0.0000,100.00,100.0,0,0,1,0");
+
+    let json = bench::to_json(&reports);
+    assert!(json.starts_with("[\n  {\"strategy\": \"astar\", \"games\": 1,"));
+    assert!(json.contains("\"deaths\": {\"edge\": 0, \"wall\": 1, \"body\": 0}}"));
+    assert!(json.ends_with("]\n"));
+}
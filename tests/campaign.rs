@@ -0,0 +1,63 @@
+use snake_rust::campaign::{Campaign, CampaignStatus, START_LIVES};
+use snake_rust::level::Level;
+
+/// Two small levels, with targets of 2 and 3 points.
+fn levels() -> Vec<Level> {
+    vec![
+        Level::parse("name: First\ntarget: 2\nlength: 3\n\n..S.....\n........\n").unwrap(),
+        Level::parse("name: Second\ntarget: 3\nlength: 3\n\n........\n..S..#..\n").unwrap(),
+    ]
+}
+
+#[test]
+fn clearing_every_level_completes_the_campaign() {
+    let mut campaign = Campaign::new(levels());
+    assert_eq!(campaign.level().name, "First");
+
+    assert_eq!(campaign.finish_level(2), CampaignStatus::PLAYING);
+    assert_eq!(campaign.level().name, "Second");
+    assert_eq!(campaign.stage(), 1);
+
+    assert_eq!(campaign.finish_level(4), CampaignStatus::COMPLETED);
+    assert_eq!(campaign.score(), 6);
+    assert_eq!(campaign.lives(), START_LIVES);
+}
+
+#[test]
+fn collisions_cost_lives() {
+    let mut campaign = Campaign::new(levels());
+
+    for lives in (1..START_LIVES).rev() {
+        assert_eq!(campaign.finish_level(1), CampaignStatus::PLAYING);
+        assert_eq!(campaign.lives(), lives);
+        // The same level is played again.
+        assert_eq!(campaign.stage(), 0);
+    }
+
+    assert_eq!(campaign.finish_level(1), CampaignStatus::LOST);
+    assert_eq!(campaign.lives(), 0);
+    // The points of every attempt count.
+    assert_eq!(campaign.score(), START_LIVES);
+}
+
+#[test]
+fn progress_round_trips_through_text() {
+    let mut campaign = Campaign::new(levels());
+    campaign.finish_level(0);
+    campaign.finish_level(5);
+
+    let resumed = Campaign::parse(&campaign.to_text(), levels()).unwrap();
+    assert_eq!(resumed, campaign);
+    assert_eq!(resumed.level().name, "Second");
+    assert_eq!(resumed.lives(), START_LIVES - 1);
+    assert_eq!(resumed.score(), 5);
+}
+
+#[test]
+fn invalid_progress_is_rejected() {
+    assert!(Campaign::parse("", levels()).is_err());
+    assert!(Campaign::parse("snake-rust-campaign 99\n", levels()).is_err());
+    assert!(Campaign::parse("snake-rust-campaign 1\nstage 2\n", levels()).is_err());
+    assert!(Campaign::parse("snake-rust-campaign 1\nlives 0\n", levels()).is_err());
+    assert!(Campaign::parse("snake-rust-campaign 1\nscore lots\n", levels()).is_err());
+}
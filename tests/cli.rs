@@ -0,0 +1,10 @@
+use snake_rust::cli::parse_value;
+
+#[test]
+fn option_values_are_parsed() {
+    assert_eq!(parse_value::<u32>("--width", Some(String::from("30"))), Ok(30));
+    assert_eq!(parse_value::<u32>("--width", None), Err(String::from("missing value for --width")));
+
+    let error = parse_value::<u32>("--width", Some(String::from("-3"))).unwrap_err();
+    assert!(error.starts_with("invalid value '-3' for --width"), "{}", error);
+}
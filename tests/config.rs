@@ -0,0 +1,43 @@
+use snake_rust::config::GameConfig;
+use snake_rust::level::Level;
+use snake_rust::replay::Replay;
+use snake_rust::sim::BoardMode;
+
+#[test]
+fn replays_are_checked_on_their_own_board() {
+    let mut config = GameConfig::new();
+    assert_eq!(config.validate(), Ok(()));
+
+    config.replay = Some(Replay::new(1, 20, 1, BoardMode::WALLS));
+    assert!(config.validate().is_err());
+
+    config.replay = Some(Replay::new(1, 20, 10, BoardMode::WALLS));
+    assert_eq!(config.validate(), Ok(()));
+}
+
+#[test]
+fn levels_may_be_smaller_than_an_open_board() {
+    let level = Level::parse("length: 3\n..S.\n....\n").unwrap();
+    let mut config = GameConfig::new();
+    config.hcells = level.hcells;
+    config.vcells = level.vcells;
+    assert!(config.validate().is_err());
+
+    config.level = Some(level);
+    assert_eq!(config.validate(), Ok(()));
+}
+
+#[test]
+fn boards_and_cells_have_a_maximum_size() {
+    let mut config = GameConfig::new();
+    config.hcells = 70_000;
+    config.vcells = 70_000;
+    assert!(config.validate().is_err());
+
+    config.hcells = 1000;
+    config.vcells = 1000;
+    assert_eq!(config.validate(), Ok(()));
+
+    config.cell_size = 1_000_000;
+    assert!(config.validate().is_err());
+}
@@ -0,0 +1,50 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use snake_rust::fonts::{find_font, font_search_dirs, FontSource};
+
+fn put_font(dir: &Path) -> PathBuf {
+    fs::create_dir_all(dir).unwrap();
+    let path = dir.join("Roboto-Regular.ttf");
+    fs::write(&path, b"not really a font").unwrap();
+    path
+}
+
+/// All in one test, since the environment is shared by every test of this file.
+#[test]
+fn fonts_are_searched_in_order() {
+    let root = env::temp_dir().join(format!("snake-rust-fonts-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+
+    env::set_var("HOME", root.join("home"));
+    env::set_var("XDG_CONFIG_HOME", root.join("config"));
+    env::set_var("XDG_DATA_HOME", root.join("data"));
+    env::set_var("XDG_DATA_DIRS", root.join("system"));
+
+    let dirs = font_search_dirs();
+    let position = |dir: PathBuf| dirs.iter().position(|d| *d == dir).unwrap();
+    assert!(position(root.join("config/snake-rust")) < position(root.join("data/fonts")));
+    assert!(position(root.join("data/fonts")) < position(root.join("system/fonts")));
+
+    // Nothing installed: the embedded font. /Library/Fonts is always searched, though.
+    if !Path::new("/Library/Fonts/Roboto-Regular.ttf").exists() {
+        assert_eq!(find_font(None), FontSource::Embedded);
+    }
+
+    // System-wide fonts are found, but the user's ones come first, and the game's own first of all.
+    let system = put_font(&root.join("system/fonts/truetype/roboto"));
+    assert_eq!(find_font(None), FontSource::File(system));
+
+    let user = put_font(&root.join("data/fonts/TTF"));
+    assert_eq!(find_font(None), FontSource::File(user));
+
+    let own = put_font(&root.join("config/snake-rust"));
+    assert_eq!(find_font(None), FontSource::File(own));
+
+    // A font given explicitly always wins.
+    let explicit = Path::new("/somewhere/else.ttf");
+    assert_eq!(find_font(Some(explicit)), FontSource::File(explicit.to_path_buf()));
+
+    fs::remove_dir_all(&root).unwrap();
+}
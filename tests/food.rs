@@ -0,0 +1,102 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use snake_rust::level::Level;
+use snake_rust::sim::food::FoodSpawner;
+use snake_rust::sim::{BoardMode, Coordinate, Direction, Simulation};
+
+const HCELLS: u32 = 6;
+const VCELLS: u32 = 4;
+
+fn cells() -> impl Iterator<Item = Coordinate> {
+    (0..VCELLS).flat_map(|y| (0..HCELLS).map(move |x| Coordinate { x, y }))
+}
+
+/// Checks the free list of `spawner` holds exactly the cells `is_free` says are free, once each,
+/// and that those are the cells not in `occupied`.
+fn assert_consistent(spawner: &FoodSpawner, occupied: &[bool]) {
+    let free = spawner.free_cells();
+
+    for (i, c) in cells().enumerate() {
+        assert_eq!(spawner.is_free(c), !occupied[i], "cell {:?}", c);
+        assert_eq!(free.iter().filter(|f| **f == c).count(), !occupied[i] as usize, "cell {:?}", c);
+    }
+
+    assert_eq!(free.len(), occupied.iter().filter(|o| !**o).count());
+}
+
+#[test]
+fn free_cells_stay_consistent() {
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    let mut spawner = FoodSpawner::new(HCELLS, VCELLS);
+    let mut occupied = vec![false; (HCELLS * VCELLS) as usize];
+    assert_consistent(&spawner, &occupied);
+
+    // Occupying or releasing a cell twice is allowed, so any sequence of them is.
+    for _ in 0..2000 {
+        let i = rng.gen_range(0..occupied.len());
+        let c = Coordinate { x: i as u32 % HCELLS, y: i as u32 / HCELLS };
+
+        if rng.gen_bool(0.5) {
+            spawner.occupy(c);
+            occupied[i] = true;
+        }
+        else {
+            spawner.release(c);
+            occupied[i] = false;
+        }
+
+        assert_consistent(&spawner, &occupied);
+
+        match spawner.spawn(&mut rng) {
+            Some(food) => assert!(spawner.is_free(food)),
+            None => assert!(occupied.iter().all(|o| *o)),
+        }
+    }
+}
+
+#[test]
+fn last_free_cell_gets_the_food() {
+    let mut rng = ChaCha8Rng::seed_from_u64(2);
+    let mut spawner = FoodSpawner::new(HCELLS, VCELLS);
+    let last = Coordinate { x: 3, y: 2 };
+
+    for c in cells().filter(|c| *c != last) {
+        spawner.occupy(c);
+    }
+
+    for _ in 0..10 {
+        assert_eq!(spawner.spawn(&mut rng), Some(last));
+    }
+
+    spawner.occupy(last);
+    assert_eq!(spawner.spawn(&mut rng), None);
+}
+
+#[test]
+fn food_never_lands_on_the_snake_or_a_wall() {
+    let level = Level::parse("\
+name: Cramped
+length: 3
+
+.#....
+..S.#.
+...#..
+#.....
+").unwrap();
+
+    let directions = [Direction::LEFT, Direction::RIGHT, Direction::UP, Direction::DOWN];
+
+    for seed in 0..50 {
+        let mut sim = Simulation::from_level(level.clone(), BoardMode::WRAP, seed).unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+
+        while !sim.over() {
+            let food = sim.food().unwrap();
+            assert!(!sim.walls().contains(&food), "seed {}", seed);
+            assert!(!sim.snake().body.contains(&food), "seed {}", seed);
+
+            sim.step(Some(directions[rng.gen_range(0..4)]));
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use snake_rust::input::TurnQueue;
+use snake_rust::sim::{BoardMode, Coordinate, Direction, Simulation};
+
+/// Plays a tick of `sim` with the next turn of `turns`.
+fn tick(sim: &mut Simulation, turns: &mut TurnQueue) {
+    let input = turns.pop();
+    sim.step(input);
+}
+
+#[test]
+fn turns_typed_within_a_tick_are_all_applied() {
+    let mut sim = Simulation::new(20, 10, BoardMode::WALLS, 1).unwrap();
+    let mut turns = TurnQueue::new();
+    let head = sim.snake().head();
+
+    // Up then left, both before the next tick: the snake goes up, then left, one per tick.
+    turns.push(Direction::UP, sim.snake().direction);
+    turns.push(Direction::LEFT, sim.snake().direction);
+
+    tick(&mut sim, &mut turns);
+    assert_eq!(sim.snake().direction, Direction::UP);
+    assert_eq!(sim.snake().head(), Coordinate { x: head.x, y: head.y - 1 });
+
+    tick(&mut sim, &mut turns);
+    assert_eq!(sim.snake().direction, Direction::LEFT);
+    assert_eq!(sim.snake().head(), Coordinate { x: head.x - 1, y: head.y - 1 });
+
+    tick(&mut sim, &mut turns);
+    assert_eq!(turns.pop(), None);
+}
+
+#[test]
+fn turns_are_checked_against_the_last_one_queued() {
+    let mut sim = Simulation::new(20, 10, BoardMode::WALLS, 1).unwrap();
+    let mut turns = TurnQueue::new();
+
+    // Heading right, left would reverse; after up is queued, down would reverse that instead.
+    turns.push(Direction::LEFT, sim.snake().direction);
+    assert_eq!(turns.pop(), None);
+
+    turns.push(Direction::UP, sim.snake().direction);
+    turns.push(Direction::DOWN, sim.snake().direction);
+    turns.push(Direction::UP, sim.snake().direction);
+    turns.push(Direction::LEFT, sim.snake().direction);
+
+    tick(&mut sim, &mut turns);
+    tick(&mut sim, &mut turns);
+    assert_eq!(sim.snake().direction, Direction::LEFT);
+    assert_eq!(sim.collision(), None);
+    assert_eq!(turns.pop(), None);
+}
+
+#[test]
+fn only_a_few_turns_are_queued() {
+    let sim = Simulation::new(20, 10, BoardMode::WALLS, 1).unwrap();
+    let mut turns = TurnQueue::new();
+    let zigzag = [Direction::UP, Direction::LEFT, Direction::DOWN, Direction::RIGHT, Direction::UP];
+
+    for direction in zigzag {
+        turns.push(direction, sim.snake().direction);
+    }
+
+    assert_eq!(turns.pop(), Some(Direction::UP));
+    assert_eq!(turns.pop(), Some(Direction::LEFT));
+    assert_eq!(turns.pop(), Some(Direction::DOWN));
+    assert_eq!(turns.pop(), None);
+
+    turns.push(Direction::UP, sim.snake().direction);
+    turns.clear();
+    assert_eq!(turns.pop(), None);
+}
@@ -0,0 +1,104 @@
+use sdl2::controller::{Axis, Button};
+use sdl2::event::Event;
+use sdl2::keyboard::{Keycode, Mod};
+
+use snake_rust::keymap::{Action, Conflict, Context, Input, Keymap, Stick, STICK_DEAD_ZONE};
+
+#[test]
+fn default_keys_do_not_conflict() {
+    let keymap = Keymap::new();
+    assert_eq!(keymap.conflicts(), vec![]);
+
+    // Return boosts in game and confirms in menus; so does A on a controller.
+    assert_eq!(keymap.action(Input::Key(Keycode::Return), Context::Game), Some(Action::Boost));
+    assert_eq!(keymap.action(Input::Key(Keycode::Return), Context::Menu), Some(Action::Confirm));
+    assert_eq!(keymap.action(Input::Pad(Button::A), Context::Game), Some(Action::Boost));
+    assert_eq!(keymap.action(Input::Pad(Button::A), Context::Menu), Some(Action::Confirm));
+    assert_eq!(keymap.action(Input::Key(Keycode::G), Context::Menu), None);
+}
+
+#[test]
+fn conflicting_keys_are_detected() {
+    let mut keymap = Keymap::new();
+    keymap.bind(Action::Pause, vec![Input::Key(Keycode::H)]);
+
+    assert_eq!(keymap.conflicts(), vec![Conflict { input: Input::Key(Keycode::H), first: Action::TurnLeft, second: Action::Pause }]);
+    assert_eq!(keymap.action(Input::Key(Keycode::H), Context::Game), Some(Action::TurnLeft));
+
+    // Actions that are never available together may share keys.
+    keymap.bind(Action::Pause, vec![Input::Key(Keycode::Space)]);
+    keymap.bind(Action::ToggleGrid, vec![Input::Key(Keycode::KpEnter)]);
+    assert_eq!(keymap.conflicts(), vec![]);
+}
+
+#[test]
+fn rebinding_keeps_the_other_device() {
+    let mut keymap = Keymap::new();
+    keymap.rebind(Action::Boost, Input::Key(Keycode::LShift));
+    assert_eq!(keymap.inputs(Action::Boost), &[Input::Pad(Button::A), Input::Key(Keycode::LShift)]);
+
+    keymap.rebind(Action::Boost, Input::Pad(Button::RightShoulder));
+    assert_eq!(keymap.inputs(Action::Boost), &[Input::Key(Keycode::LShift), Input::Pad(Button::RightShoulder)]);
+}
+
+#[test]
+fn keymap_round_trips_through_text() {
+    let mut keymap = Keymap::new();
+    keymap.bind(Action::TurnLeft, vec![Input::Key(Keycode::A), Input::Pad(Button::LeftShoulder)]);
+    keymap.bind(Action::ToggleGrid, vec![]);
+
+    assert_eq!(Keymap::parse(&keymap.to_text()), Ok(keymap));
+
+    let partial = Keymap::parse("# Comment\n\nboost = Left Shift, pad:x\n").unwrap();
+    assert_eq!(partial.inputs(Action::Boost), &[Input::Key(Keycode::LShift), Input::Pad(Button::X)]);
+    assert_eq!(partial.inputs(Action::Pause), Keymap::new().inputs(Action::Pause));
+
+    assert!(Keymap::parse("jump = Space\n").is_err());
+    assert!(Keymap::parse("boost = NotAKey\n").is_err());
+    assert!(Keymap::parse("boost = pad:z\n").is_err());
+    assert!(Keymap::parse("boost\n").is_err());
+}
+
+#[test]
+fn stick_presses_the_dpad_past_the_dead_zone() {
+    let mut stick = Stick::new();
+
+    // Within the dead zone nothing happens.
+    assert_eq!(stick.move_axis(Axis::LeftX, STICK_DEAD_ZONE - 1), None);
+    assert_eq!(stick.move_axis(Axis::LeftX, i16::MAX), Some(Button::DPadRight));
+    // Holding the stick there does not press again.
+    assert_eq!(stick.move_axis(Axis::LeftX, i16::MAX - 10), None);
+    // The axis pushed the most wins.
+    assert_eq!(stick.move_axis(Axis::LeftY, i16::MIN), Some(Button::DPadUp));
+    // Other axes are ignored.
+    assert_eq!(stick.move_axis(Axis::RightX, i16::MIN), None);
+
+    // Back in the middle, the same direction can be pressed again.
+    assert_eq!(stick.move_axis(Axis::LeftX, 0), None);
+    assert_eq!(stick.move_axis(Axis::LeftY, 0), None);
+    assert_eq!(stick.move_axis(Axis::LeftY, i16::MIN), Some(Button::DPadUp));
+}
+
+#[test]
+fn controller_events_map_to_actions() {
+    let keymap = Keymap::new();
+    let action = |event: &Event, context| Input::pressed(event).and_then(|input| keymap.action(input, context));
+
+    let press = Event::ControllerButtonDown { timestamp: 0, which: 0, button: Button::X };
+    assert_eq!(action(&press, Context::Game), Some(Action::ToggleGrid));
+    assert_eq!(action(&press, Context::Menu), None);
+
+    let release = Event::ControllerButtonUp { timestamp: 0, which: 0, button: Button::A };
+    assert_eq!(action(&release, Context::Game), None);
+    assert_eq!(Input::released(&release), Some(Input::Pad(Button::A)));
+
+    // The stick steers like the D-pad.
+    let mut stick = Stick::new();
+    let button = stick.move_axis(Axis::LeftY, i16::MAX).unwrap();
+    let press = Event::ControllerButtonDown { timestamp: 0, which: 0, button };
+    assert_eq!(action(&press, Context::Game), Some(Action::TurnDown));
+
+    let key = Event::KeyDown { timestamp: 0, window_id: 0, keycode: Some(Keycode::Space), scancode: None,
+                               keymod: Mod::NOMOD, repeat: false };
+    assert_eq!(action(&key, Context::Game), Some(Action::Pause));
+}
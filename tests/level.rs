@@ -0,0 +1,109 @@
+use snake_rust::level::Level;
+use snake_rust::sim::{BoardMode, Collision, Coordinate, Direction, Simulation, StepOutcome};
+
+/// A wall right in front of the `Snake`, and a single spot for the food behind it.
+const WALLED: &str = "\
+name: Walled
+direction: right
+length: 3
+
+..........
+..S..#....
+.........F
+";
+
+#[test]
+fn bundled_levels_are_valid() {
+    let levels = Level::bundled();
+    assert!(!levels.is_empty());
+
+    for level in levels {
+        assert_eq!(level.validate(), Ok(()), "{} is not valid", level.name);
+        assert_eq!(Level::parse(&level.to_text()), Ok(level.clone()));
+        assert_eq!(Level::find_bundled(&level.name.to_uppercase()), Some(level));
+    }
+}
+
+#[test]
+fn level_is_parsed_from_text() {
+    let level = Level::parse(WALLED).unwrap();
+
+    assert_eq!(level.name, "Walled");
+    assert_eq!((level.hcells, level.vcells), (10, 3));
+    assert_eq!(level.walls, vec![Coordinate { x: 5, y: 1 }]);
+    assert_eq!(level.spawn, Coordinate { x: 2, y: 1 });
+    assert_eq!(level.direction, Direction::RIGHT);
+    assert_eq!(level.snake_body(), vec![Coordinate { x: 2, y: 1 },
+                                        Coordinate { x: 1, y: 1 },
+                                        Coordinate { x: 0, y: 1 }]);
+    assert_eq!(level.food_spots, vec![Coordinate { x: 9, y: 2 }]);
+}
+
+#[test]
+fn invalid_levels_are_rejected() {
+    // No spawn point.
+    assert!(Level::parse("....\n.##.\n").is_err());
+    // Two spawn points.
+    assert!(Level::parse("S...\n...S\n").is_err());
+    // The body does not fit behind the spawn point.
+    assert!(Level::parse("length: 4\n..S.\n").is_err());
+    // The body lies on a wall.
+    assert!(Level::parse("length: 3\n#.S.\n").is_err());
+    // The body is longer than the area; this must not build it.
+    assert!(Level::parse("length: 4000000000\n..S.\n").is_err());
+    // Unknown cell.
+    assert!(Level::parse("..S.x\n").is_err());
+}
+
+#[test]
+fn grids_with_blank_lines_are_rejected() {
+    let error = Level::parse("name: Gap\n\n..S...\n\n......\n").unwrap_err();
+    assert_eq!(error, "line 4: blank line in the grid");
+
+    // Blank lines around the grid are fine.
+    assert!(Level::parse("\n\n......S.\n........\n\n\n").is_ok());
+}
+
+#[test]
+fn ragged_grids_are_rejected() {
+    let error = Level::parse("..S...\n....\n......\n").unwrap_err();
+    assert!(error.starts_with("line 2: "), "{}", error);
+
+    let error = Level::parse("..S...\n......\n.........\n").unwrap_err();
+    assert!(error.starts_with("line 3: "), "{}", error);
+}
+
+#[test]
+fn walls_end_the_game() {
+    let level = Level::parse(WALLED).unwrap();
+
+    for mode in [BoardMode::WALLS, BoardMode::WRAP] {
+        let mut sim = Simulation::from_level(level.clone(), mode, 1).unwrap();
+
+        assert_eq!(sim.step(None), StepOutcome::MOVED);
+        assert_eq!(sim.step(None), StepOutcome::MOVED);
+        assert_eq!(sim.step(None), StepOutcome::COLLIDED);
+        assert_eq!(sim.collision(), Some(Collision::WALL));
+    }
+}
+
+#[test]
+fn food_only_appears_on_food_spots() {
+    let level = Level::parse(WALLED).unwrap();
+
+    for seed in 0..20 {
+        let sim = Simulation::from_level(level.clone(), BoardMode::WALLS, seed).unwrap();
+        assert_eq!(sim.food(), Some(Coordinate { x: 9, y: 2 }));
+    }
+}
+
+#[test]
+fn food_never_appears_on_walls() {
+    for level in Level::bundled() {
+        for seed in 0..50 {
+            let sim = Simulation::from_level(level.clone(), BoardMode::WALLS, seed).unwrap();
+            let food = sim.food().unwrap();
+            assert!(!sim.walls().contains(&food), "food on a wall in {}", level.name);
+        }
+    }
+}
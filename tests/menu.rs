@@ -0,0 +1,91 @@
+use snake_rust::menu::{Menu, MenuEvent, MenuId};
+
+/// A main page with an action, a selector and a submenu holding another action.
+fn build_menu() -> Menu {
+    let mut menu = Menu::new("Main");
+    menu.action("New Game", MenuId::NEWGAME)
+        .selector("Mode", MenuId::MODE, vec![String::from("Walls"), String::from("Wrap"), String::from("Open")], 1)
+        .submenu("Options")
+            .action("Controls", MenuId::CONTROLS)
+        .end_submenu()
+        .action("Exit", MenuId::EXIT);
+    menu
+}
+
+#[test]
+fn moving_wraps_around_the_page() {
+    let mut menu = build_menu();
+    assert_eq!(menu.current(), 0);
+
+    menu.up();
+    assert_eq!(menu.current(), 3);
+    menu.down();
+    assert_eq!(menu.current(), 0);
+    menu.down();
+    menu.down();
+    assert_eq!(menu.current(), 2);
+}
+
+#[test]
+fn selectors_cycle_through_their_values() {
+    let mut menu = build_menu();
+    menu.down();
+    assert_eq!(menu.labels()[1], "Mode: < Wrap >");
+
+    assert_eq!(menu.change(1), MenuEvent::CHANGED(MenuId::MODE, 2));
+    assert_eq!(menu.change(1), MenuEvent::CHANGED(MenuId::MODE, 0));
+    assert_eq!(menu.change(-1), MenuEvent::CHANGED(MenuId::MODE, 2));
+    assert_eq!(menu.select(), MenuEvent::CHANGED(MenuId::MODE, 0));
+    assert_eq!(menu.labels()[1], "Mode: < Walls >");
+
+    // Only selectors have values to change.
+    menu.up();
+    assert_eq!(menu.change(1), MenuEvent::NONE);
+    assert_eq!(menu.select(), MenuEvent::SELECTED(MenuId::NEWGAME));
+}
+
+#[test]
+fn submenus_are_entered_and_left() {
+    let mut menu = build_menu();
+    menu.hover(2);
+    assert_eq!(menu.select(), MenuEvent::NONE);
+    assert_eq!(menu.title(), "Options");
+    assert!(!menu.at_top());
+    assert_eq!(menu.labels(), vec!["Controls", "Back"]);
+    assert_eq!(menu.select(), MenuEvent::SELECTED(MenuId::CONTROLS));
+
+    // Through the back entry...
+    menu.down();
+    assert_eq!(menu.select(), MenuEvent::NONE);
+    assert_eq!(menu.title(), "Main");
+    assert_eq!(menu.current(), 2);
+
+    // ...or the back key, which leaves the menu from the main page.
+    menu.select();
+    assert_eq!(menu.back_out(), MenuEvent::NONE);
+    assert!(menu.at_top());
+    assert_eq!(menu.back_out(), MenuEvent::CLOSED);
+}
+
+#[test]
+fn hovering_selects_existing_entries_only() {
+    let mut menu = build_menu();
+    assert!(menu.hover(3));
+    assert_eq!(menu.current(), 3);
+    assert_eq!(menu.select(), MenuEvent::SELECTED(MenuId::EXIT));
+
+    assert!(!menu.hover(4));
+    assert_eq!(menu.current(), 3);
+}
+
+#[test]
+fn empty_pages_do_nothing() {
+    let mut menu = Menu::new("Empty");
+    menu.up();
+    menu.down();
+    assert_eq!(menu.current(), 0);
+    assert!(!menu.hover(0));
+    assert_eq!(menu.change(1), MenuEvent::NONE);
+    assert_eq!(menu.select(), MenuEvent::NONE);
+    assert_eq!(menu.back_out(), MenuEvent::CLOSED);
+}
@@ -0,0 +1,65 @@
+use snake_rust::replay::Replay;
+use snake_rust::sim::{BoardMode, Direction, Simulation, StepOutcome};
+
+/// Plays a short scripted game, recording it the same way the game does.
+fn record_game() -> Replay {
+    let mut sim = Simulation::new(20, 10, BoardMode::WRAP, 7).unwrap();
+    let mut replay = Replay::for_simulation(&sim);
+    let script = [Direction::DOWN, Direction::LEFT, Direction::UP, Direction::LEFT];
+
+    for input in script.iter().cycle().take(200) {
+        let tick = sim.ticks();
+        let direction = sim.snake().direction;
+        let outcome = sim.step(Some(*input));
+
+        if sim.snake().direction != direction {
+            replay.record(tick, sim.snake().direction);
+        }
+
+        if outcome == StepOutcome::COLLIDED {
+            break;
+        }
+    }
+
+    replay.finish(sim.ticks(), sim.score());
+    replay
+}
+
+#[test]
+fn replay_round_trips_through_text() {
+    let replay = record_game();
+    let text = replay.to_text();
+    let parsed = Replay::parse(&text).unwrap();
+
+    assert_eq!(parsed, replay);
+    assert_eq!(parsed.to_text(), text);
+}
+
+#[test]
+fn replay_plays_back_the_same_game() {
+    let mut replay = record_game();
+    assert_eq!(replay.verify(), Ok(()));
+
+    let (ticks, score) = replay.end.unwrap();
+    replay.finish(ticks, score + 1);
+    assert!(replay.verify().is_err());
+}
+
+#[test]
+fn invalid_replays_are_rejected() {
+    assert!(Replay::parse("hello").is_err());
+    assert!(Replay::parse("snake-rust-replay 999\nseed 1\nboard 20 10\n").is_err());
+    assert!(Replay::parse("snake-rust-replay 1\nseed 1\n").is_err());
+    assert!(Replay::parse("snake-rust-replay 1\nseed 1\nboard 20 10\nturn 3 SIDEWAYS\n").is_err());
+}
+
+#[test]
+fn boards_too_small_for_the_snake_are_rejected() {
+    for board in ["0 0", "3 3", "4 10"] {
+        let text = format!("snake-rust-replay 1\nseed 1\nboard {}\nend 10 0\n", board);
+        let error = Replay::parse(&text).unwrap_err();
+        assert!(error.starts_with("line 3: "), "{}", error);
+    }
+
+    assert!(Replay::parse("snake-rust-replay 1\nseed 1\nboard 10 3\nend 10 0\n").is_ok());
+}
@@ -0,0 +1,79 @@
+use std::time::Duration;
+
+use snake_rust::scores::{self, Board, HighScore, HighScores, MAX_ENTRIES};
+use snake_rust::sim::BoardMode;
+
+fn board(mode: BoardMode) -> Board {
+    Board { mode, hcells: 38, vcells: 28, level: String::from("Open") }
+}
+
+fn entry(name: &str, score: u32) -> HighScore {
+    HighScore {
+        name    : String::from(name),
+        score,
+        length  : score + 5,
+        duration: Duration::from_millis(1500 * score as u64),
+        seed    : 42,
+        date    : 1717236000,
+    }
+}
+
+#[test]
+fn tables_keep_the_best_scores_in_order() {
+    let walls = board(BoardMode::WALLS);
+    let mut scores = HighScores::new();
+
+    for score in 1..=MAX_ENTRIES as u32 + 5 {
+        scores.insert(&walls, entry("Alice", score));
+    }
+
+    let table = scores.table(&walls);
+    assert_eq!(table.len(), MAX_ENTRIES);
+    assert_eq!(table[0].score, MAX_ENTRIES as u32 + 5);
+    assert!(table.windows(2).all(|w| w[0].score >= w[1].score));
+
+    // Not good enough to get in, nor is a game without points.
+    assert_eq!(scores.rank(&walls, 3), None);
+    assert_eq!(HighScores::new().rank(&walls, 0), None);
+
+    // A tie goes behind the older entry.
+    assert_eq!(scores.insert(&walls, entry("Bob", MAX_ENTRIES as u32 + 5)), Some(1));
+}
+
+#[test]
+fn each_board_has_its_own_table() {
+    let mut scores = HighScores::new();
+    scores.insert(&board(BoardMode::WALLS), entry("Alice", 10));
+
+    assert_eq!(scores.table(&board(BoardMode::WALLS)).len(), 1);
+    assert!(scores.table(&board(BoardMode::WRAP)).is_empty());
+    assert_eq!(scores.rank(&board(BoardMode::WRAP), 1), Some(0));
+}
+
+#[test]
+fn high_scores_round_trip_through_text() {
+    let mut scores = HighScores::new();
+    scores.insert(&board(BoardMode::WALLS), entry("Alice", 10));
+    scores.insert(&board(BoardMode::WALLS), entry("Bob the Builder", 7));
+    scores.insert(&Board { level: String::from("Two Rooms"), ..board(BoardMode::WRAP) }, entry("Carol", 3));
+
+    assert_eq!(HighScores::parse(&scores.to_text()), Ok(scores));
+}
+
+#[test]
+fn invalid_high_scores_are_rejected() {
+    assert!(HighScores::parse("").is_err());
+    assert!(HighScores::parse("snake-rust-scores 2\n").is_err());
+    assert!(HighScores::parse("snake-rust-scores 1\nentry 1 6 100 1 1 Alice\n").is_err());
+    assert!(HighScores::parse("snake-rust-scores 1\ntable SIDEWAYS 38 28 Open\n").is_err());
+    assert!(HighScores::parse("snake-rust-scores 1\ntable WALLS 38 28 Open\n\
+                               entry 1 6 100 1 1 Alice\nentry 2 7 100 1 1 Bob\n").is_err());
+}
+
+#[test]
+fn dates_and_durations_are_readable() {
+    assert_eq!(scores::format_date(0), "1970-01-01");
+    assert_eq!(scores::format_date(1717236000), "2024-06-01");
+    assert_eq!(scores::format_date(951782400), "2000-02-29");
+    assert_eq!(scores::format_duration(Duration::from_millis(125_900)), "2:05");
+}
@@ -0,0 +1,194 @@
+use snake_rust::level::Level;
+use snake_rust::sim::{create_snake, BoardMode, Cell, Collision, Coordinate, Direction, Simulation, StepOutcome};
+
+const HCELLS: u32 = 20;
+const VCELLS: u32 = 10;
+
+/// Plays until the `Snake` dies, returning the number of ticks it survived.
+fn play_until_collision(sim: &mut Simulation, input: Option<Direction>) -> u32 {
+    let mut ticks = 0;
+
+    while sim.step(input) != StepOutcome::COLLIDED {
+        ticks += 1;
+        assert!(ticks < HCELLS * VCELLS, "the snake should have hit a wall by now");
+    }
+
+    ticks
+}
+
+#[test]
+fn second_game_starts_clean() {
+    let mut sim = Simulation::new(HCELLS, VCELLS, BoardMode::WALLS, 1).unwrap();
+    play_until_collision(&mut sim, Some(Direction::UP));
+
+    sim.reset(2);
+
+    let fresh = create_snake(HCELLS, VCELLS);
+    assert_eq!(sim.score(), 0);
+    assert_eq!(sim.snake().direction, Direction::RIGHT);
+    assert_eq!(sim.snake().body, fresh.body);
+
+    // The new game is not stuck in the collided state of the previous one.
+    assert_ne!(sim.step(None), StepOutcome::COLLIDED);
+}
+
+#[test]
+fn tiny_board_can_be_won() {
+    // On a 8x2 board the `Snake` starts on the bottom row, heading right. Going around the
+    // border of the board visits every cell, so it never collides and eventually eats it all.
+    let mut sim = Simulation::new(8, 2, BoardMode::WALLS, 1).unwrap();
+    let mut ticks = 0;
+
+    let outcome = loop {
+        let head = sim.snake().head();
+        let input = match (head.x, head.y) {
+            (7, 1) => Direction::UP,
+            (0, 0) => Direction::DOWN,
+            (_, 0) => Direction::LEFT,
+            _      => Direction::RIGHT,
+        };
+
+        let outcome = sim.step(Some(input));
+        if outcome != StepOutcome::MOVED && outcome != StepOutcome::ATE {
+            break outcome;
+        }
+
+        ticks += 1;
+        assert!(ticks < 1000, "the snake should have filled the board by now");
+    };
+
+    assert_eq!(outcome, StepOutcome::WON);
+    assert_eq!(sim.snake().body.len(), 16);
+    assert_eq!(sim.score(), 11);
+    assert_eq!(sim.food(), None);
+}
+
+#[test]
+fn boards_too_small_for_the_snake_are_rejected() {
+    // The `Snake` starts in the middle with 5 cells, so it needs 8 cells across.
+    assert!(Simulation::new(6, 6, BoardMode::WALLS, 1).is_err());
+    assert!(Simulation::new(0, 0, BoardMode::WRAP, 1).is_err());
+    assert!(Simulation::new(8, 1, BoardMode::WALLS, 1).is_ok());
+}
+
+#[test]
+fn boards_too_large_are_rejected() {
+    assert!(Simulation::new(70_000, 70_000, BoardMode::WALLS, 1).is_err());
+    assert!(Simulation::new(u32::MAX, u32::MAX, BoardMode::WRAP, 1).is_err());
+    assert!(Simulation::new(1024, 1024, BoardMode::WALLS, 1).is_ok());
+}
+
+#[test]
+fn same_seed_same_game() {
+    let mut first  = Simulation::new(HCELLS, VCELLS, BoardMode::WALLS, 42).unwrap();
+    let mut second = Simulation::new(HCELLS, VCELLS, BoardMode::WALLS, 42).unwrap();
+    let inputs = [None, Some(Direction::DOWN), None, Some(Direction::LEFT), None, None];
+
+    for input in inputs.iter().cycle().take(100) {
+        assert_eq!(first.food(), second.food());
+
+        let outcome = first.step(*input);
+        assert_eq!(outcome, second.step(*input));
+        assert_eq!(first.snake().body, second.snake().body);
+
+        if outcome == StepOutcome::COLLIDED {
+            break;
+        }
+    }
+}
+
+#[test]
+fn wrap_mode_re_enters_on_the_opposite_side() {
+    let mut sim = Simulation::new(HCELLS, VCELLS, BoardMode::WRAP, 1).unwrap();
+
+    // Going right from the middle, the head reaches the right edge and comes back on the left.
+    for _ in 0..HCELLS {
+        assert_ne!(sim.step(None), StepOutcome::COLLIDED);
+    }
+    assert_eq!(sim.snake().head().x, HCELLS / 2);
+
+    // Same going up through the top edge.
+    for _ in 0..VCELLS {
+        assert_ne!(sim.step(Some(Direction::UP)), StepOutcome::COLLIDED);
+    }
+    assert_eq!(sim.snake().head().y, VCELLS / 2);
+}
+
+#[test]
+fn collisions_tell_what_was_hit() {
+    let mut sim = Simulation::new(HCELLS, VCELLS, BoardMode::WALLS, 1).unwrap();
+    assert_eq!(sim.collision(), None);
+    play_until_collision(&mut sim, None);
+    assert_eq!(sim.collision(), Some(Collision::EDGE));
+
+    // Down, left and up again makes the head run into the body.
+    sim.reset(1);
+    sim.step(Some(Direction::DOWN));
+    sim.step(Some(Direction::LEFT));
+    assert_eq!(sim.step(Some(Direction::UP)), StepOutcome::COLLIDED);
+    assert_eq!(sim.collision(), Some(Collision::BODY));
+
+    sim.reset(1);
+    assert_eq!(sim.collision(), None);
+    assert_eq!(sim.food_eaten(), 0);
+}
+
+#[test]
+fn collisions_leave_the_snake_whole() {
+    let mut sim = Simulation::new(HCELLS, VCELLS, BoardMode::WALLS, 1).unwrap();
+    sim.step(Some(Direction::DOWN));
+    sim.step(Some(Direction::LEFT));
+
+    let body = sim.snake().body.clone();
+    assert_eq!(sim.step(Some(Direction::UP)), StepOutcome::COLLIDED);
+    assert_eq!(sim.snake().body, body);
+
+    // Four cells can chase their own tail around a square, since the tail leaves as the head
+    // comes in.
+    let level = Level::parse("length: 4\n....S..F\n........\n").unwrap();
+    let mut sim = Simulation::from_level(level, BoardMode::WALLS, 1).unwrap();
+    for direction in [Direction::DOWN, Direction::LEFT, Direction::UP, Direction::RIGHT].iter().cycle().take(12) {
+        assert_eq!(sim.step(Some(*direction)), StepOutcome::MOVED);
+    }
+    assert_eq!(sim.snake().body.len(), 4);
+}
+
+#[test]
+fn bots_see_every_cell() {
+    let mut sim = Simulation::new(HCELLS, VCELLS, BoardMode::WALLS, 1).unwrap();
+    let view = sim.view();
+    let cells = view.cells();
+    let head = sim.snake().head();
+    let food = sim.food().unwrap();
+
+    assert_eq!(cells.len(), (HCELLS * VCELLS) as usize);
+    assert_eq!(view.cell(head), Cell::HEAD(0));
+    assert_eq!(view.cell(sim.snake().body[1]), Cell::BODY(0));
+    assert_eq!(view.cell(food), Cell::FOOD);
+    for c in [head, sim.snake().body[1], food] {
+        assert_eq!(cells[(c.y * HCELLS + c.x) as usize], view.cell(c));
+    }
+    assert_eq!(cells.iter().filter(|c| **c == Cell::EMPTY).count(), (HCELLS * VCELLS) as usize - sim.snake().body.len() - 1);
+
+    // Acting is stepping with a direction; turning back is still ignored.
+    assert_eq!(sim.act(Direction::LEFT), StepOutcome::MOVED);
+    assert_eq!(sim.snake().head(), Coordinate { x: head.x + 1, y: head.y });
+    assert!(!sim.over());
+
+    play_until_collision(&mut sim, Some(Direction::UP));
+    assert!(sim.over());
+}
+
+#[test]
+fn directions_and_modes_have_names() {
+    for direction in [Direction::LEFT, Direction::RIGHT, Direction::UP, Direction::DOWN] {
+        assert_eq!(Direction::from_name(direction.name()), Some(direction));
+    }
+    assert_eq!(Direction::from_name("left"), Some(Direction::LEFT));
+    assert_eq!(Direction::from_name("sideways"), None);
+
+    for mode in [BoardMode::WALLS, BoardMode::WRAP] {
+        assert_eq!(BoardMode::from_name(mode.name()), Some(mode));
+    }
+    assert_eq!(BoardMode::from_name("MAZE"), None);
+}
@@ -0,0 +1,124 @@
+use snake_rust::level::Level;
+use snake_rust::sim::versus::{Match, RoundOutcome, Versus, MAX_PLAYERS, ROUNDS_TO_WIN};
+use snake_rust::sim::{BoardMode, Collision, Coordinate, Direction};
+
+/// An open board `hcells` wide, with snakes of 3 cells and the food out of their way, in a corner.
+fn board(hcells: u32) -> Level {
+    Level {
+        length      : 3,
+        food_spots  : vec![Coordinate { x: 0, y: 0 }],
+        ..Level::open(hcells, 4)
+    }
+}
+
+fn play(versus: &mut Versus, inputs: &[[Option<Direction>; 2]]) -> Vec<RoundOutcome> {
+    inputs.iter().map(|step| versus.step(step)).collect()
+}
+
+#[test]
+fn snakes_spawn_apart() {
+    for players in 2..=MAX_PLAYERS {
+        let versus = Versus::new(Level::open(38, 28), BoardMode::WALLS, players, 1).unwrap();
+        let snakes: Vec<_> = versus.players().iter().map(|p| p.snake()).collect();
+
+        assert_eq!(snakes.len(), players);
+        for (i, a) in snakes.iter().enumerate() {
+            for b in &snakes[i + 1..] {
+                assert!(a.body.iter().all(|c| !b.body.contains(c)));
+            }
+        }
+    }
+
+    assert!(Versus::new(Level::open(38, 28), BoardMode::WALLS, 1, 1).is_err());
+    assert!(Versus::new(Level::open(38, 28), BoardMode::WALLS, MAX_PLAYERS + 1, 1).is_err());
+    assert!(Versus::new(Level::open(8, 2), BoardMode::WALLS, 4, 1).is_err());
+    assert!(Versus::new(Level::open(70_000, 70_000), BoardMode::WALLS, 2, 1).is_err());
+}
+
+#[test]
+fn heads_meeting_in_a_cell_both_die() {
+    let mut versus = Versus::new(board(12), BoardMode::WALLS, 2, 1).unwrap();
+
+    let outcomes = play(&mut versus, &[
+        [None, Some(Direction::UP)],
+        [None, Some(Direction::LEFT)],
+        [None, None],
+    ]);
+
+    assert_eq!(outcomes, vec![RoundOutcome::PLAYING, RoundOutcome::PLAYING, RoundOutcome::DRAW]);
+    assert!(versus.players().iter().all(|p| p.collision() == Some(Collision::HEAD)));
+}
+
+#[test]
+fn heads_passing_through_each_other_both_die() {
+    let mut versus = Versus::new(board(11), BoardMode::WALLS, 2, 1).unwrap();
+
+    let outcomes = play(&mut versus, &[
+        [None, Some(Direction::UP)],
+        [None, Some(Direction::LEFT)],
+        [None, None],
+        [None, None],
+    ]);
+
+    assert_eq!(outcomes.last(), Some(&RoundOutcome::DRAW));
+    assert!(versus.players().iter().all(|p| p.collision() == Some(Collision::HEAD)));
+}
+
+/// The second snake runs into the body of the first one, which wins the round.
+const HEAD_TO_BODY: [[Option<Direction>; 2]; 4] = [
+    [None, None],
+    [None, None],
+    [None, None],
+    [None, Some(Direction::UP)],
+];
+
+#[test]
+fn running_into_another_body_loses_the_round() {
+    let mut versus = Versus::new(board(12), BoardMode::WALLS, 2, 1).unwrap();
+
+    let outcomes = play(&mut versus, &HEAD_TO_BODY);
+
+    assert_eq!(outcomes.last(), Some(&RoundOutcome::WON(0)));
+    assert_eq!(versus.players()[0].collision(), None);
+    assert_eq!(versus.players()[1].collision(), Some(Collision::SNAKE));
+
+    // Once the round is over, nothing moves.
+    let head = versus.players()[0].snake().head();
+    assert_eq!(versus.step(&[None, None]), RoundOutcome::WON(0));
+    assert_eq!(versus.players()[0].snake().head(), head);
+}
+
+#[test]
+fn dead_snakes_stay_whole() {
+    let mut versus = Versus::new(board(12), BoardMode::WALLS, 2, 1).unwrap();
+
+    play(&mut versus, &HEAD_TO_BODY[..3]);
+    let body = versus.players()[1].snake().body.clone();
+    play(&mut versus, &HEAD_TO_BODY[3..]);
+
+    assert_eq!(versus.players()[1].collision(), Some(Collision::SNAKE));
+    assert_eq!(versus.players()[1].snake().body, body);
+}
+
+#[test]
+fn match_is_won_after_enough_rounds() {
+    let mut versus = Versus::new(board(12), BoardMode::WALLS, 2, 1).unwrap();
+    let mut scoreboard = Match::new(2);
+
+    // A round still being played does not count.
+    scoreboard.record(&versus);
+    assert_eq!(scoreboard.rounds(), 0);
+
+    for round in 1..=ROUNDS_TO_WIN {
+        assert_eq!(scoreboard.winner(), None);
+
+        versus.reset(round as u64);
+        play(&mut versus, &HEAD_TO_BODY);
+        scoreboard.record(&versus);
+    }
+
+    assert_eq!(scoreboard.rounds(), ROUNDS_TO_WIN);
+    assert_eq!(scoreboard.wins(0), ROUNDS_TO_WIN);
+    assert_eq!(scoreboard.wins(1), 0);
+    assert_eq!(scoreboard.winner(), Some(0));
+}